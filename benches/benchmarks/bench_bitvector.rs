@@ -96,6 +96,52 @@ fn bench_rank(c: &mut Criterion) {
     });
 }
 
+fn bench_select1(c: &mut Criterion) {
+    let mut bv = generate_bitvector(BITVECTOR_LARGE);
+
+    // Calculate the counts and select samples for our bitvector
+    bv.calculate_counts();
+
+    let ones = bv.rank(BITVECTOR_LARGE);
+
+    c.bench_function("bench_select1", |b| {
+        b.iter_batched(
+            // Create a new list of set bits to find
+            || generate_indices(BITVECTOR_LARGE / 1_000, 0 .. ones),
+            // Run the benchmark for those set bits
+            |indices| {
+                for k in indices {
+                    bv.select1(k);
+                }
+            },
+            BatchSize::SmallInput
+        )
+    });
+}
+
+fn bench_select0(c: &mut Criterion) {
+    let mut bv = generate_bitvector(BITVECTOR_LARGE);
+
+    // Calculate the counts and select samples for our bitvector
+    bv.calculate_counts();
+
+    let zeros = bv.rank0(BITVECTOR_LARGE);
+
+    c.bench_function("bench_select0", |b| {
+        b.iter_batched(
+            // Create a new list of unset bits to find
+            || generate_indices(BITVECTOR_LARGE / 1_000, 0 .. zeros),
+            // Run the benchmark for those unset bits
+            |indices| {
+                for k in indices {
+                    bv.select0(k);
+                }
+            },
+            BatchSize::SmallInput
+        )
+    });
+}
+
 // TODO: https://bheisler.github.io/criterion.rs/book/user_guide/advanced_configuration.html
 
 fn custom_criterion_config() -> Criterion {
@@ -107,5 +153,5 @@ fn custom_criterion_config() -> Criterion {
 criterion_group!(
     name = benches;
     config = custom_criterion_config();
-    targets = bench_get, bench_index, bench_rank
+    targets = bench_get, bench_index, bench_rank, bench_select1, bench_select0
 );
@@ -7,7 +7,9 @@ use criterion::{
 };
 use rust_fm::{
     alphabet::DNAAlphabet,
-    index::fm_index::FMIndex
+    index::fm_index::FMIndex,
+    matrix::BandedMatrix,
+    myers::MyersMatrix
 };
 
 use crate::util::generator::AlphabetGenerator;
@@ -17,6 +19,12 @@ const NEW_FM_INDEX_SIZE: usize = 1_000_000_000;
 const MATCH_AMOUNT_OF_CHARACTERS: usize = 1_000_000;
 const MATCH_PATTERN_SIZE: usize = 100;
 
+const APPROXIMATE_MATCH_PATTERN_SIZE: usize = 20;
+const APPROXIMATE_MATCH_ERRORS: usize = 2;
+
+const LONG_APPROXIMATE_MATCH_PATTERN_SIZE: usize = 100;
+const LONG_APPROXIMATE_MATCH_ERRORS: usize = 4;
+
 const SAMPLE_SIZE: usize = 1_000;
 const MEASUREMENT_TIME: u64 = 20;
 
@@ -50,6 +58,87 @@ fn bench_exact_match(c: &mut Criterion) {
     });
 }
 
+fn bench_hamming_match(c: &mut Criterion) {
+    let generator = AlphabetGenerator::<DNAAlphabet>::default();
+
+    let fm_index =
+        FMIndex::<DNAAlphabet>::new(generator.generate_string(MATCH_AMOUNT_OF_CHARACTERS), 1);
+
+    c.bench_function("bench_hamming_match", |b| {
+        b.iter_batched_ref(
+            || generator.generate_pattern(APPROXIMATE_MATCH_PATTERN_SIZE),
+            |mut pattern| fm_index.hamming_match(&mut pattern, APPROXIMATE_MATCH_ERRORS),
+            BatchSize::SmallInput
+        )
+    });
+}
+
+fn bench_approximate_match(c: &mut Criterion) {
+    let generator = AlphabetGenerator::<DNAAlphabet>::default();
+
+    let fm_index =
+        FMIndex::<DNAAlphabet>::new(generator.generate_string(MATCH_AMOUNT_OF_CHARACTERS), 1);
+
+    c.bench_function("bench_approximate_match", |b| {
+        b.iter_batched_ref(
+            || generator.generate_pattern(APPROXIMATE_MATCH_PATTERN_SIZE),
+            |mut pattern| fm_index.approximate_match(&mut pattern, APPROXIMATE_MATCH_ERRORS),
+            BatchSize::SmallInput
+        )
+    });
+}
+
+fn bench_approximate_match_myers(c: &mut Criterion) {
+    let generator = AlphabetGenerator::<DNAAlphabet>::default();
+
+    let fm_index =
+        FMIndex::<DNAAlphabet>::new(generator.generate_string(MATCH_AMOUNT_OF_CHARACTERS), 1);
+
+    c.bench_function("bench_approximate_match_myers", |b| {
+        b.iter_batched_ref(
+            || generator.generate_pattern(APPROXIMATE_MATCH_PATTERN_SIZE),
+            |mut pattern| {
+                fm_index
+                    .approximate_match_with::<MyersMatrix>(&mut pattern, APPROXIMATE_MATCH_ERRORS)
+            },
+            BatchSize::SmallInput
+        )
+    });
+}
+
+fn bench_approximate_match_long(c: &mut Criterion) {
+    let generator = AlphabetGenerator::<DNAAlphabet>::default();
+
+    let fm_index =
+        FMIndex::<DNAAlphabet>::new(generator.generate_string(MATCH_AMOUNT_OF_CHARACTERS), 1);
+
+    c.bench_function("bench_approximate_match_long_banded", |b| {
+        b.iter_batched_ref(
+            || generator.generate_pattern(LONG_APPROXIMATE_MATCH_PATTERN_SIZE),
+            |mut pattern| {
+                fm_index.approximate_match_with::<BandedMatrix>(
+                    &mut pattern,
+                    LONG_APPROXIMATE_MATCH_ERRORS
+                )
+            },
+            BatchSize::SmallInput
+        )
+    });
+
+    c.bench_function("bench_approximate_match_long_myers", |b| {
+        b.iter_batched_ref(
+            || generator.generate_pattern(LONG_APPROXIMATE_MATCH_PATTERN_SIZE),
+            |mut pattern| {
+                fm_index.approximate_match_with::<MyersMatrix>(
+                    &mut pattern,
+                    LONG_APPROXIMATE_MATCH_ERRORS
+                )
+            },
+            BatchSize::SmallInput
+        )
+    });
+}
+
 // TODO: https://bheisler.github.io/criterion.rs/book/user_guide/advanced_configuration.html
 
 fn custom_criterion_config() -> Criterion {
@@ -61,5 +150,6 @@ fn custom_criterion_config() -> Criterion {
 criterion_group!(
     name = benches;
     config = custom_criterion_config();
-    targets = /*bench_new,*/ bench_exact_match
+    targets = /*bench_new,*/ bench_exact_match, bench_hamming_match, bench_approximate_match,
+        bench_approximate_match_myers, bench_approximate_match_long
 );
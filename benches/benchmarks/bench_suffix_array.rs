@@ -53,7 +53,7 @@ fn bench_from_sa(c: &mut Criterion) {
             || generate_suffix_array(AMOUNT_OF_INDICES, vec![b'A', b'C', b'G', b'T']),
             // Run the benchmark for those indices
             |suffix_array| {
-                SparseSuffixArray::from_sa(suffix_array, 32);
+                SparseSuffixArray::<u32>::from_sa(suffix_array, 32);
             },
             BatchSize::SmallInput
         )
@@ -17,7 +17,7 @@ pub struct AlphabetGenerator<A: Alphabet> {
     alphabet: A
 }
 
-impl<A: Alphabet> AlphabetGenerator<A> {
+impl<A: Alphabet + Default> AlphabetGenerator<A> {
     pub fn generate_indices(&self, amount: usize) -> Vec<AlphabetIndex> {
         let mut rng = rand::thread_rng();
 
@@ -51,7 +51,7 @@ impl<A: Alphabet> AlphabetGenerator<A> {
     }
 }
 
-impl<A: Alphabet> Default for AlphabetGenerator<A> {
+impl<A: Alphabet + Default> Default for AlphabetGenerator<A> {
     fn default() -> Self {
         Self {
             alphabet: Default::default()
@@ -1,7 +1,10 @@
-use std::ops::{
-    Deref,
-    DerefMut,
-    Index
+use std::{
+    ops::{
+        Deref,
+        DerefMut,
+        Index
+    },
+    str::FromStr
 };
 
 use serde::{
@@ -9,6 +12,15 @@ use serde::{
     Serialize
 };
 
+use crate::{
+    errors::{
+        Error,
+        ErrorKind,
+        Result
+    },
+    parallel
+};
+
 pub type AlphabetChar = u8;
 pub type AlphabetIndex = u8;
 
@@ -16,14 +28,32 @@ pub type AlphabetIndex = u8;
 // == Alphabet
 // ======================================================================
 
-pub trait Alphabet: Default {
+pub trait Alphabet: Clone + Send + Sync {
     fn i2c(&self, i: AlphabetIndex) -> AlphabetChar;
-    fn c2i(&self, c: AlphabetChar) -> AlphabetIndex;
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex>;
     fn len(&self) -> usize;
     fn bits(&self) -> usize;
+
+    fn c2i(&self, c: AlphabetChar) -> AlphabetIndex {
+        match self.try_c2i(c) {
+            Some(i) => i,
+            None => panic!("'{}' is not a part of the alphabet!", c)
+        }
+    }
+
+    /// Expand a possibly ambiguous character into all indices it stands for. Alphabets without
+    /// ambiguity codes only expand their own characters.
+    fn expand(&self, c: AlphabetChar) -> Option<Vec<AlphabetIndex>> {
+        return self.try_c2i(c).map(|i| vec![i]);
+    }
+
+    /// Get the complement of a character. Alphabets without complements return `None`.
+    fn complement(&self, _i: AlphabetIndex) -> Option<AlphabetIndex> {
+        return None;
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct DNAAlphabet;
 
 impl Alphabet for DNAAlphabet {
@@ -37,18 +67,14 @@ impl Alphabet for DNAAlphabet {
         };
     }
 
-    fn c2i(&self, c: AlphabetChar) -> AlphabetIndex {
-        let i = match c {
-            b'A' => 0,
-            b'C' => 1,
-            b'G' => 2,
-            b'T' => 3,
-            _ => 4
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex> {
+        return match c {
+            b'A' => Some(0),
+            b'C' => Some(1),
+            b'G' => Some(2),
+            b'T' => Some(3),
+            _ => None
         };
-
-        assert!(i < 4, "'{}' is not a part of the alphabet!", c);
-
-        return i;
     }
 
     fn len(&self) -> usize {
@@ -58,6 +84,34 @@ impl Alphabet for DNAAlphabet {
     fn bits(&self) -> usize {
         return 2;
     }
+
+    /// Expand the IUPAC nucleotide codes
+    fn expand(&self, c: AlphabetChar) -> Option<Vec<AlphabetIndex>> {
+        return match c {
+            b'A' => Some(vec![0]),
+            b'C' => Some(vec![1]),
+            b'G' => Some(vec![2]),
+            b'T' => Some(vec![3]),
+            b'R' => Some(vec![0, 2]),
+            b'Y' => Some(vec![1, 3]),
+            b'S' => Some(vec![1, 2]),
+            b'W' => Some(vec![0, 3]),
+            b'K' => Some(vec![2, 3]),
+            b'M' => Some(vec![0, 1]),
+            b'B' => Some(vec![1, 2, 3]),
+            b'D' => Some(vec![0, 2, 3]),
+            b'H' => Some(vec![0, 1, 3]),
+            b'V' => Some(vec![0, 1, 2]),
+            b'N' => Some(vec![0, 1, 2, 3]),
+            _ => None
+        };
+    }
+
+    /// A <-> T and C <-> G
+    fn complement(&self, i: AlphabetIndex) -> Option<AlphabetIndex> {
+        assert!(i < 4, "The alphabet contains only 4 characters!");
+        return Some(3 - i);
+    }
 }
 
 impl Default for DNAAlphabet {
@@ -66,10 +120,324 @@ impl Default for DNAAlphabet {
     }
 }
 
+/// The 20 standard amino acids
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProteinAlphabet;
+
+impl Alphabet for ProteinAlphabet {
+    fn i2c(&self, i: AlphabetIndex) -> AlphabetChar {
+        assert!(i < 20, "The alphabet contains only 20 characters!");
+        return match i {
+            0 => b'A',
+            1 => b'C',
+            2 => b'D',
+            3 => b'E',
+            4 => b'F',
+            5 => b'G',
+            6 => b'H',
+            7 => b'I',
+            8 => b'K',
+            9 => b'L',
+            10 => b'M',
+            11 => b'N',
+            12 => b'P',
+            13 => b'Q',
+            14 => b'R',
+            15 => b'S',
+            16 => b'T',
+            17 => b'V',
+            18 => b'W',
+            _ => b'Y'
+        };
+    }
+
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex> {
+        return match c {
+            b'A' => Some(0),
+            b'C' => Some(1),
+            b'D' => Some(2),
+            b'E' => Some(3),
+            b'F' => Some(4),
+            b'G' => Some(5),
+            b'H' => Some(6),
+            b'I' => Some(7),
+            b'K' => Some(8),
+            b'L' => Some(9),
+            b'M' => Some(10),
+            b'N' => Some(11),
+            b'P' => Some(12),
+            b'Q' => Some(13),
+            b'R' => Some(14),
+            b'S' => Some(15),
+            b'T' => Some(16),
+            b'V' => Some(17),
+            b'W' => Some(18),
+            b'Y' => Some(19),
+            _ => None
+        };
+    }
+
+    fn len(&self) -> usize {
+        return 20;
+    }
+
+    fn bits(&self) -> usize {
+        return 5;
+    }
+}
+
+impl Default for ProteinAlphabet {
+    fn default() -> Self {
+        ProteinAlphabet
+    }
+}
+
+/// The 20 standard amino acids extended with the ambiguity codes B, Z and X and the rare amino
+/// acids U (selenocysteine) and O (pyrrolysine)
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ExtendedProteinAlphabet;
+
+impl Alphabet for ExtendedProteinAlphabet {
+    fn i2c(&self, i: AlphabetIndex) -> AlphabetChar {
+        assert!(i < 25, "The alphabet contains only 25 characters!");
+
+        // All uppercase letters except for J
+        if i < 9 {
+            return b'A' + i;
+        }
+        return b'A' + i + 1;
+    }
+
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex> {
+        return match c {
+            b'A' ..= b'I' => Some(c - b'A'),
+            b'K' ..= b'Z' => Some(c - b'A' - 1),
+            _ => None
+        };
+    }
+
+    fn len(&self) -> usize {
+        return 25;
+    }
+
+    fn bits(&self) -> usize {
+        return 5;
+    }
+}
+
+impl Default for ExtendedProteinAlphabet {
+    fn default() -> Self {
+        ExtendedProteinAlphabet
+    }
+}
+
+/// Alphabet defined at runtime from a list of characters
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct CustomAlphabet {
+    /// The characters of the alphabet, ordered by their index
+    characters: Vec<AlphabetChar>,
+
+    /// Lookup table from characters (and their aliases) to indices
+    indices: Vec<Option<AlphabetIndex>>,
+
+    /// Whether lowercase and uppercase characters are treated as equal
+    case_insensitive: bool
+}
+
+impl CustomAlphabet {
+    /// Create a new alphabet from a list of characters. At most 255 characters are allowed, the
+    /// last index stays free for the separator of a `SeparatedAlphabet` and the sentinel of the
+    /// external construction.
+    pub fn new(characters: &[AlphabetChar], case_insensitive: bool) -> Result<Self> {
+        if characters.is_empty() {
+            bail!(ErrorKind::InvalidAlphabet("the alphabet contains no characters".to_string()));
+        }
+
+        if characters.len() > AlphabetIndex::MAX as usize {
+            bail!(ErrorKind::InvalidAlphabet(format!(
+                "the alphabet contains more than {} characters, index {} is reserved for \
+                 separators and sentinels",
+                AlphabetIndex::MAX,
+                AlphabetIndex::MAX
+            )));
+        }
+
+        let mut alphabet = Self {
+            characters:       characters.to_vec(),
+            indices:          vec![None; 256],
+            case_insensitive: case_insensitive
+        };
+
+        for (i, c) in characters.iter().enumerate() {
+            alphabet.insert(*c, i as AlphabetIndex)?;
+        }
+
+        return Ok(alphabet);
+    }
+
+    /// Parse an alphabet specification like `ACGT U=T`. The first word lists the characters of
+    /// the alphabet, every following word of the form `X=Y` makes `X` an alias for `Y`.
+    pub fn from_specification(specification: &str, case_insensitive: bool) -> Result<Self> {
+        let mut words = specification.split_whitespace();
+
+        let characters = words.next().unwrap_or_default();
+        let mut alphabet = Self::new(characters.as_bytes(), case_insensitive)?;
+
+        for word in words {
+            match word.as_bytes() {
+                [alias, b'=', c] => alphabet.add_alias(*alias, *c)?,
+                _ => bail!(ErrorKind::InvalidAlphabet(format!("'{}' is not a valid alias", word)))
+            }
+        }
+
+        return Ok(alphabet);
+    }
+
+    /// Map an additional character onto an existing character of the alphabet
+    pub fn add_alias(&mut self, alias: AlphabetChar, c: AlphabetChar) -> Result<()> {
+        match self.indices[c as usize] {
+            Some(i) => self.insert(alias, i),
+            None => bail!(ErrorKind::InvalidAlphabet(format!(
+                "'{}' is not a part of the alphabet",
+                c as char
+            )))
+        }
+    }
+
+    /// Register a character for an index, folding the case if needed
+    fn insert(&mut self, c: AlphabetChar, i: AlphabetIndex) -> Result<()> {
+        let variants = if self.case_insensitive {
+            vec![c.to_ascii_uppercase(), c.to_ascii_lowercase()]
+        } else {
+            vec![c]
+        };
+
+        for variant in variants {
+            match self.indices[variant as usize] {
+                Some(j) if j != i => bail!(ErrorKind::InvalidAlphabet(format!(
+                    "'{}' occurs more than once",
+                    variant as char
+                ))),
+                _ => self.indices[variant as usize] = Some(i)
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl Alphabet for CustomAlphabet {
+    fn i2c(&self, i: AlphabetIndex) -> AlphabetChar {
+        assert!(
+            (i as usize) < self.characters.len(),
+            "The alphabet contains only {} characters!",
+            self.characters.len()
+        );
+        return self.characters[i as usize];
+    }
+
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex> {
+        return self.indices[c as usize];
+    }
+
+    fn len(&self) -> usize {
+        return self.characters.len();
+    }
+
+    fn bits(&self) -> usize {
+        let bits = (usize::BITS - (self.characters.len() - 1).leading_zeros()) as usize;
+        return bits.max(1);
+    }
+}
+
+/// Alphabet extended with a separator character that sorts after all other characters. Patterns
+/// never contain the separator, so no match can cross it.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SeparatedAlphabet<A: Alphabet> {
+    /// The alphabet without the separator
+    pub inner: A
+}
+
+impl<A: Alphabet> SeparatedAlphabet<A> {
+    /// Character used to print the separator
+    pub const SEPARATOR: AlphabetChar = b'$';
+
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner: inner
+        }
+    }
+
+    /// Get the index of the separator
+    pub fn separator(&self) -> AlphabetIndex {
+        return self.inner.len() as AlphabetIndex;
+    }
+}
+
+impl<A: Alphabet> Alphabet for SeparatedAlphabet<A> {
+    fn i2c(&self, i: AlphabetIndex) -> AlphabetChar {
+        if i == self.separator() {
+            return Self::SEPARATOR;
+        }
+        return self.inner.i2c(i);
+    }
+
+    fn try_c2i(&self, c: AlphabetChar) -> Option<AlphabetIndex> {
+        return self.inner.try_c2i(c);
+    }
+
+    fn len(&self) -> usize {
+        return self.inner.len() + 1;
+    }
+
+    fn bits(&self) -> usize {
+        return (usize::BITS - self.inner.len().leading_zeros()) as usize;
+    }
+
+    fn expand(&self, c: AlphabetChar) -> Option<Vec<AlphabetIndex>> {
+        return self.inner.expand(c);
+    }
+
+    fn complement(&self, i: AlphabetIndex) -> Option<AlphabetIndex> {
+        if i == self.separator() {
+            return None;
+        }
+        return self.inner.complement(i);
+    }
+}
+
+impl<A: Alphabet + Default> Default for SeparatedAlphabet<A> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
 // ======================================================================
 // == AlphabetString
 // ======================================================================
 
+/// What to do with characters that are not a part of the alphabet
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InvalidCharacterPolicy {
+    /// Fail with an error
+    Reject,
+
+    /// Leave the character out
+    Skip,
+
+    /// Replace the character by another character of the alphabet
+    Replace(AlphabetChar),
+
+    /// Split the string at the character
+    Split
+}
+
+impl Default for InvalidCharacterPolicy {
+    fn default() -> Self {
+        InvalidCharacterPolicy::Reject
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AlphabetString<A: Alphabet> {
     bytes: Vec<AlphabetIndex>,
@@ -78,13 +446,121 @@ pub struct AlphabetString<A: Alphabet> {
 }
 
 impl<A: Alphabet> AlphabetString<A> {
-    pub fn new(n: usize) -> Self {
+    /// Create a new string of length n over a given alphabet
+    pub fn with_alphabet(n: usize, alphabet: A) -> Self {
         let bytes: Vec<AlphabetIndex> = vec![0; n];
 
         Self {
             bytes:    bytes,
-            alphabet: Default::default()
+            alphabet: alphabet
+        }
+    }
+
+    /// Translate a string of characters using a given alphabet
+    pub fn from_alphabet(string: &str, alphabet: A) -> Self {
+        Self {
+            bytes:    string.bytes().map(|c| alphabet.c2i(c)).collect(),
+            alphabet: alphabet
+        }
+    }
+
+    /// Translate a string of characters using a given alphabet, failing on invalid characters
+    pub fn try_from_alphabet(string: &str, alphabet: A) -> Result<Self> {
+        let mut bytes = Vec::with_capacity(string.len());
+
+        for (i, c) in string.bytes().enumerate() {
+            match alphabet.try_c2i(c) {
+                Some(char_i) => bytes.push(char_i),
+                None => bail!(ErrorKind::InvalidCharacter(i, c))
+            }
+        }
+
+        return Ok(Self {
+            bytes:    bytes,
+            alphabet: alphabet
+        });
+    }
+
+    /// Translate a string of characters using a given alphabet, handling invalid characters
+    /// according to the policy. Only the split policy can result in more than one string.
+    pub fn parse(string: &str, alphabet: A, policy: InvalidCharacterPolicy) -> Result<Vec<Self>> {
+        let replacement = match policy {
+            InvalidCharacterPolicy::Replace(c) => match alphabet.try_c2i(c) {
+                Some(char_i) => Some(char_i),
+                None => bail!(ErrorKind::InvalidAlphabet(format!(
+                    "replacement '{}' is not a part of the alphabet",
+                    c as char
+                )))
+            },
+            _ => None
+        };
+
+        let mut strings = vec![];
+        let mut bytes = Vec::with_capacity(string.len());
+
+        let mut split = false;
+
+        for (i, c) in string.bytes().enumerate() {
+            if let Some(char_i) = alphabet.try_c2i(c) {
+                bytes.push(char_i);
+                continue;
+            }
+
+            match policy {
+                InvalidCharacterPolicy::Reject => bail!(ErrorKind::InvalidCharacter(i, c)),
+                InvalidCharacterPolicy::Skip => {}
+                InvalidCharacterPolicy::Replace(_) => bytes.extend(replacement),
+                InvalidCharacterPolicy::Split => {
+                    split = true;
+
+                    if !bytes.is_empty() {
+                        strings.push(Self {
+                            bytes:    bytes,
+                            alphabet: alphabet.clone()
+                        });
+                    }
+
+                    bytes = vec![];
+                }
+            }
         }
+
+        // Splitting never produces empty strings, a string without invalid characters is kept as
+        // is, even when it is empty
+        if !bytes.is_empty() || !split {
+            strings.push(Self {
+                bytes:    bytes,
+                alphabet: alphabet
+            });
+        }
+
+        return Ok(strings);
+    }
+
+    /// Get the reverse complement of the string, if the alphabet supports complements
+    pub fn reverse_complement(&self) -> Option<Self> {
+        let bytes = self
+            .bytes
+            .iter()
+            .rev()
+            .map(|char_i| self.alphabet.complement(*char_i))
+            .collect::<Option<Vec<AlphabetIndex>>>()?;
+
+        return Some(Self {
+            bytes:    bytes,
+            alphabet: self.alphabet.clone()
+        });
+    }
+}
+
+impl<A: Alphabet + Default> AlphabetString<A> {
+    pub fn new(n: usize) -> Self {
+        Self::with_alphabet(n, Default::default())
+    }
+
+    /// Fallible counterpart of `From<&str>`
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        Self::try_from_alphabet(string, Default::default())
     }
 }
 
@@ -104,99 +580,448 @@ impl<A: Alphabet> DerefMut for AlphabetString<A> {
     }
 }
 
-impl<A: Alphabet> From<&str> for AlphabetString<A> {
+impl<A: Alphabet + Default> From<&str> for AlphabetString<A> {
     fn from(string: &str) -> Self {
-        let alphabet: A = Default::default();
-
-        Self {
-            bytes:    string.bytes().map(|c| alphabet.c2i(c)).collect(),
-            alphabet: alphabet
-        }
+        Self::from_alphabet(string, Default::default())
     }
 }
 
-// ======================================================================
-// == AlphabetPattern
-// ======================================================================
+// `TryFrom<&str>` is taken by the standard blanket implementation over `From<&str>`, so the
+// fallible conversion from a `&str` goes through `str::parse`
+impl<A: Alphabet + Default> FromStr for AlphabetString<A> {
+    type Err = Error;
 
-pub enum Direction {
-    FORWARD,
-    BACKWARD
+    fn from_str(string: &str) -> Result<Self> {
+        Self::try_from_str(string)
+    }
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::FORWARD
+impl<A: Alphabet + Default> TryFrom<String> for AlphabetString<A> {
+    type Error = Error;
+
+    fn try_from(string: String) -> Result<Self> {
+        Self::try_from_str(&string)
     }
 }
 
-pub struct AlphabetPattern<A: Alphabet> {
-    pattern: AlphabetString<A>,
+// ======================================================================
+// == PackedAlphabetString
+// ======================================================================
 
-    pattern_length: usize,
+/// String of alphabet indices that only uses `Alphabet::bits()` bits per character
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PackedAlphabetString<A: Alphabet> {
+    /// Amount of characters in the string
+    n: usize,
 
-    direction: Direction
+    /// Amount of bits per character
+    bits: usize,
+
+    /// The packed characters, a character never spans two words
+    words: Vec<u64>,
+
+    pub alphabet: A
 }
 
-impl<A: Alphabet> AlphabetPattern<A> {
-    pub fn new(pattern: &str, direction: Direction) -> Self {
+impl<A: Alphabet> PackedAlphabetString<A> {
+    /// Create a new string of length n over a given alphabet
+    pub fn with_alphabet(n: usize, alphabet: A) -> Self {
+        let bits = alphabet.bits();
+        let characters_per_word = 64 / bits;
+
         Self {
-            pattern:        AlphabetString::<A>::from(pattern),
-            pattern_length: pattern.len(),
-            direction:      direction
+            n:        n,
+            bits:     bits,
+            words:    vec![0; n.div_ceil(characters_per_word)],
+            alphabet: alphabet
         }
     }
 
-    pub fn direction(&self) -> &Direction {
-        &self.direction
+    /// Concatenate strings over an alphabet, every string but the last one must fill its last
+    /// word
+    pub fn concat(parts: Vec<Self>, alphabet: A) -> Self {
+        let mut string = Self::with_alphabet(0, alphabet);
+
+        for part in parts {
+            assert!(
+                string.n.is_multiple_of(string.characters_per_word()),
+                "Only strings that fill their last word can be extended!"
+            );
+
+            string.n += part.n;
+            string.words.extend(part.words);
+        }
+
+        return string;
     }
 
-    pub fn set_direction(&mut self, direction: Direction) {
-        self.direction = direction;
+    /// Get the character at a given position
+    pub fn get(&self, i: usize) -> AlphabetIndex {
+        let (word, shift) = self.locate(i);
+        return ((self.words[word] >> shift) & self.mask()) as AlphabetIndex;
+    }
+
+    /// Set the character at a given position
+    pub fn set(&mut self, i: usize, char_i: AlphabetIndex) {
+        let (word, shift) = self.locate(i);
+        let mask = self.mask();
+
+        self.words[word] &= !(mask << shift);
+        self.words[word] |= (char_i as u64 & mask) << shift;
     }
 
+    /// Get the length of the string
     pub fn len(&self) -> usize {
-        return self.pattern_length;
+        return self.n;
     }
-}
 
-impl<A: Alphabet> Index<usize> for AlphabetPattern<A> {
-    type Output = AlphabetIndex;
+    /// Check whether the string is empty
+    pub fn is_empty(&self) -> bool {
+        return self.n == 0;
+    }
 
-    fn index(&self, i: usize) -> &Self::Output {
-        match self.direction {
-            Direction::FORWARD => &self.pattern[i],
-            Direction::BACKWARD => &self.pattern[self.pattern_length - i - 1]
+    /// Get the amount of bytes used by the packed characters
+    pub fn size_in_bytes(&self) -> usize {
+        return self.words.len() * std::mem::size_of::<u64>();
+    }
+
+    /// Get the amount of characters packed in a single word
+    pub fn characters_per_word(&self) -> usize {
+        return 64 / self.bits;
+    }
+
+    /// Iterate over the characters of the string
+    pub fn iter(&self) -> PackedAlphabetStringIter<'_, A> {
+        PackedAlphabetStringIter {
+            string: self,
+            i:      0
         }
     }
+
+    /// Find the word and the offset within that word of a position
+    fn locate(&self, i: usize) -> (usize, usize) {
+        assert!(i < self.n, "Position {} is out of bounds for length {}!", i, self.n);
+
+        let characters_per_word = self.characters_per_word();
+        return (i / characters_per_word, (i % characters_per_word) * self.bits);
+    }
+
+    fn mask(&self) -> u64 {
+        return (1 << self.bits) - 1;
+    }
 }
 
-impl<A: Alphabet> From<&str> for AlphabetPattern<A> {
-    fn from(string: &str) -> Self {
-        Self {
-            pattern:        AlphabetString::<A>::from(string),
-            pattern_length: string.len(),
-            direction:      Default::default()
-        }
+impl<A: Alphabet + Default> PackedAlphabetString<A> {
+    pub fn new(n: usize) -> Self {
+        Self::with_alphabet(n, Default::default())
     }
 }
 
-// ======================================================================
-// == Tests
-// ======================================================================
+impl<A: Alphabet> From<&AlphabetString<A>> for PackedAlphabetString<A> {
+    fn from(string: &AlphabetString<A>) -> Self {
+        let characters_per_word = 64 / string.alphabet.bits();
+
+        let parts = parallel::map_chunks(string.len(), characters_per_word, |chunk| {
+            let mut packed = Self::with_alphabet(chunk.len(), string.alphabet.clone());
+
+            for (i, char_i) in string[chunk].iter().enumerate() {
+                packed.set(i, *char_i);
+            }
+
+            return packed;
+        });
+
+        return Self::concat(parts, string.alphabet.clone());
+    }
+}
+
+impl<A: Alphabet + Default> From<&str> for PackedAlphabetString<A> {
+    fn from(string: &str) -> Self {
+        Self::from(&AlphabetString::<A>::from(string))
+    }
+}
+
+impl<'a, A: Alphabet> IntoIterator for &'a PackedAlphabetString<A> {
+    type IntoIter = PackedAlphabetStringIter<'a, A>;
+    type Item = AlphabetIndex;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the characters of a packed string
+pub struct PackedAlphabetStringIter<'a, A: Alphabet> {
+    string: &'a PackedAlphabetString<A>,
+
+    i: usize
+}
+
+impl<'a, A: Alphabet> Iterator for PackedAlphabetStringIter<'a, A> {
+    type Item = AlphabetIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i >= self.string.len() {
+            return None;
+        }
+
+        self.i += 1;
+        return Some(self.string.get(self.i - 1));
+    }
+}
+
+// ======================================================================
+// == AlphabetPattern
+// ======================================================================
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    FORWARD,
+    BACKWARD
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::FORWARD
+    }
+}
+
+pub struct AlphabetPattern<A: Alphabet> {
+    pattern: AlphabetString<A>,
+
+    pattern_length: usize,
+
+    direction: Direction
+}
+
+impl<A: Alphabet + Default> AlphabetPattern<A> {
+    pub fn new(pattern: &str, direction: Direction) -> Self {
+        Self {
+            pattern:        AlphabetString::<A>::from(pattern),
+            pattern_length: pattern.len(),
+            direction:      direction
+        }
+    }
+
+    pub fn try_new(pattern: &str, direction: Direction) -> Result<Self> {
+        let mut pattern = Self::try_from_str(pattern)?;
+        pattern.set_direction(direction);
+
+        return Ok(pattern);
+    }
+
+    /// Fallible counterpart of `From<&str>`
+    pub fn try_from_str(pattern: &str) -> Result<Self> {
+        Ok(Self::from(AlphabetString::<A>::try_from_str(pattern)?))
+    }
+}
+
+impl<A: Alphabet> AlphabetPattern<A> {
+    pub fn direction(&self) -> &Direction {
+        &self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.pattern_length;
+    }
+
+    /// Get the characters from `start` up to `end` of the original pattern as a new pattern,
+    /// read in `direction`
+    pub fn slice(&self, start: usize, end: usize, direction: Direction) -> Self {
+        Self {
+            pattern:        AlphabetString {
+                bytes:    self.pattern.bytes[start .. end].to_vec(),
+                alphabet: self.pattern.alphabet.clone()
+            },
+            pattern_length: end - start,
+            direction:      direction
+        }
+    }
+
+    /// Get the reverse complement of the pattern, if the alphabet supports complements
+    pub fn reverse_complement(&self) -> Option<Self> {
+        Some(Self {
+            pattern:        self.pattern.reverse_complement()?,
+            pattern_length: self.pattern_length,
+            direction:      self.direction
+        })
+    }
+}
+
+impl<A: Alphabet> Index<usize> for AlphabetPattern<A> {
+    type Output = AlphabetIndex;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        match self.direction {
+            Direction::FORWARD => &self.pattern[i],
+            Direction::BACKWARD => &self.pattern[self.pattern_length - i - 1]
+        }
+    }
+}
+
+impl<A: Alphabet + Default> From<&str> for AlphabetPattern<A> {
+    fn from(string: &str) -> Self {
+        Self::from(AlphabetString::<A>::from(string))
+    }
+}
+
+impl<A: Alphabet + Default> FromStr for AlphabetPattern<A> {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Self> {
+        Self::try_from_str(pattern)
+    }
+}
+
+impl<A: Alphabet + Default> TryFrom<String> for AlphabetPattern<A> {
+    type Error = Error;
+
+    fn try_from(pattern: String) -> Result<Self> {
+        Self::try_from_str(&pattern)
+    }
+}
+
+impl<A: Alphabet> From<AlphabetString<A>> for AlphabetPattern<A> {
+    fn from(string: AlphabetString<A>) -> Self {
+        Self {
+            pattern_length: string.len(),
+            pattern:        string,
+            direction:      Default::default()
+        }
+    }
+}
+
+// ======================================================================
+// == AmbiguousPattern
+// ======================================================================
+
+/// Pattern in which every position can stand for a set of characters.
+///
+/// This is a separate type rather than a variant of `AlphabetPattern`: the exact match of an
+/// `AlphabetPattern` narrows down a single range, while a set of characters splits it into a
+/// range per concrete sequence. Ambiguous patterns are therefore matched by
+/// `ambiguous_exact_match`, which reports every concrete sequence along with its range.
+pub struct AmbiguousPattern<A: Alphabet> {
+    /// The allowed characters for every position
+    positions: Vec<Vec<AlphabetIndex>>,
+
+    /// The alphabet of the characters
+    alphabet: A,
+
+    direction: Direction
+}
+
+impl<A: Alphabet> AmbiguousPattern<A> {
+    /// Translate a string of (ambiguous) characters using a given alphabet
+    pub fn from_alphabet(pattern: &str, alphabet: A) -> Self {
+        match Self::try_from_alphabet(pattern, alphabet) {
+            Ok(pattern) => pattern,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Translate a string of (ambiguous) characters using a given alphabet, failing on invalid
+    /// characters
+    pub fn try_from_alphabet(pattern: &str, alphabet: A) -> Result<Self> {
+        let mut positions = Vec::with_capacity(pattern.len());
+
+        for (i, c) in pattern.bytes().enumerate() {
+            match alphabet.expand(c) {
+                Some(characters) => positions.push(characters),
+                None => bail!(ErrorKind::InvalidCharacter(i, c))
+            }
+        }
+
+        return Ok(Self {
+            positions: positions,
+            alphabet:  alphabet,
+            direction: Default::default()
+        });
+    }
+
+    pub fn alphabet(&self) -> &A {
+        &self.alphabet
+    }
+
+    pub fn direction(&self) -> &Direction {
+        &self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.positions.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.positions.is_empty();
+    }
+}
+
+impl<A: Alphabet + Default> AmbiguousPattern<A> {
+    pub fn new(pattern: &str, direction: Direction) -> Self {
+        let mut pattern = Self::from(pattern);
+        pattern.set_direction(direction);
+
+        return pattern;
+    }
+}
+
+impl<A: Alphabet> Index<usize> for AmbiguousPattern<A> {
+    type Output = Vec<AlphabetIndex>;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        match self.direction {
+            Direction::FORWARD => &self.positions[i],
+            Direction::BACKWARD => &self.positions[self.positions.len() - i - 1]
+        }
+    }
+}
+
+impl<A: Alphabet + Default> From<&str> for AmbiguousPattern<A> {
+    fn from(string: &str) -> Self {
+        Self::from_alphabet(string, Default::default())
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
 
 #[cfg(test)]
 mod tests {
-    use crate::alphabet::{
-        Alphabet,
-        AlphabetChar,
-        AlphabetIndex,
-        DNAAlphabet
+    use crate::{
+        alphabet::{
+            Alphabet,
+            AlphabetChar,
+            AlphabetIndex,
+            AlphabetPattern,
+            AlphabetString,
+            AmbiguousPattern,
+            CustomAlphabet,
+            DNAAlphabet,
+            Direction,
+            ExtendedProteinAlphabet,
+            InvalidCharacterPolicy,
+            PackedAlphabetString,
+            ProteinAlphabet,
+            SeparatedAlphabet
+        },
+        errors::ErrorKind
     };
 
     const DNA_CHARACTERS: [AlphabetChar; 4] = [b'A', b'C', b'G', b'T'];
     const DNA_INDICES: [AlphabetIndex; 4] = [0, 1, 2, 3];
 
+    const PROTEIN_CHARACTERS: &[u8; 20] = b"ACDEFGHIKLMNPQRSTVWY";
+    const EXTENDED_PROTEIN_CHARACTERS: &[u8; 25] = b"ABCDEFGHIKLMNOPQRSTUVWXYZ";
+
     #[test]
     fn test_dna_alphabet_c2i() {
         let alphabet = DNAAlphabet::default();
@@ -224,4 +1049,460 @@ mod tests {
     fn test_dna_alphabet_bits() {
         assert_eq!(DNAAlphabet::default().bits(), 2)
     }
+
+    #[test]
+    fn test_protein_alphabet_c2i() {
+        let alphabet = ProteinAlphabet::default();
+
+        for i in 0 .. PROTEIN_CHARACTERS.len() {
+            assert_eq!(alphabet.c2i(PROTEIN_CHARACTERS[i]), i as AlphabetIndex);
+        }
+    }
+
+    #[test]
+    fn test_protein_alphabet_i2c() {
+        let alphabet = ProteinAlphabet::default();
+
+        for i in 0 .. PROTEIN_CHARACTERS.len() {
+            assert_eq!(alphabet.i2c(i as AlphabetIndex), PROTEIN_CHARACTERS[i]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_protein_alphabet_c2i_invalid() {
+        ProteinAlphabet::default().c2i(b'X');
+    }
+
+    #[test]
+    fn test_protein_alphabet_len() {
+        assert_eq!(ProteinAlphabet::default().len(), 20)
+    }
+
+    #[test]
+    fn test_protein_alphabet_bits() {
+        assert_eq!(ProteinAlphabet::default().bits(), 5)
+    }
+
+    #[test]
+    fn test_extended_protein_alphabet_c2i() {
+        let alphabet = ExtendedProteinAlphabet::default();
+
+        for i in 0 .. EXTENDED_PROTEIN_CHARACTERS.len() {
+            assert_eq!(alphabet.c2i(EXTENDED_PROTEIN_CHARACTERS[i]), i as AlphabetIndex);
+        }
+    }
+
+    #[test]
+    fn test_extended_protein_alphabet_i2c() {
+        let alphabet = ExtendedProteinAlphabet::default();
+
+        for i in 0 .. EXTENDED_PROTEIN_CHARACTERS.len() {
+            assert_eq!(alphabet.i2c(i as AlphabetIndex), EXTENDED_PROTEIN_CHARACTERS[i]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_extended_protein_alphabet_c2i_invalid() {
+        ExtendedProteinAlphabet::default().c2i(b'J');
+    }
+
+    #[test]
+    fn test_extended_protein_alphabet_len() {
+        assert_eq!(ExtendedProteinAlphabet::default().len(), 25)
+    }
+
+    #[test]
+    fn test_extended_protein_alphabet_bits() {
+        assert_eq!(ExtendedProteinAlphabet::default().bits(), 5)
+    }
+
+    #[test]
+    fn test_custom_alphabet_c2i() {
+        let alphabet = CustomAlphabet::new(b"ACGT", false).unwrap();
+
+        for i in 0 .. DNA_CHARACTERS.len() {
+            assert_eq!(alphabet.c2i(DNA_CHARACTERS[i]), DNA_INDICES[i]);
+        }
+    }
+
+    #[test]
+    fn test_custom_alphabet_i2c() {
+        let alphabet = CustomAlphabet::new(b"ACGT", false).unwrap();
+
+        for i in 0 .. DNA_INDICES.len() {
+            assert_eq!(alphabet.i2c(DNA_INDICES[i]), DNA_CHARACTERS[i]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_custom_alphabet_c2i_invalid() {
+        CustomAlphabet::new(b"ACGT", false).unwrap().c2i(b'a');
+    }
+
+    #[test]
+    fn test_custom_alphabet_len() {
+        assert_eq!(CustomAlphabet::new(b"ACGT", false).unwrap().len(), 4);
+        assert_eq!(
+            CustomAlphabet::new(PROTEIN_CHARACTERS, false)
+                .unwrap()
+                .len(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_custom_alphabet_bits() {
+        assert_eq!(CustomAlphabet::new(b"A", false).unwrap().bits(), 1);
+        assert_eq!(CustomAlphabet::new(b"AC", false).unwrap().bits(), 1);
+        assert_eq!(CustomAlphabet::new(b"ACG", false).unwrap().bits(), 2);
+        assert_eq!(CustomAlphabet::new(b"ACGT", false).unwrap().bits(), 2);
+        assert_eq!(CustomAlphabet::new(b"ACGTN", false).unwrap().bits(), 3);
+        assert_eq!(
+            CustomAlphabet::new(PROTEIN_CHARACTERS, false)
+                .unwrap()
+                .bits(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_custom_alphabet_case_folding() {
+        let alphabet = CustomAlphabet::new(b"ACGT", true).unwrap();
+
+        for i in 0 .. DNA_CHARACTERS.len() {
+            assert_eq!(alphabet.c2i(DNA_CHARACTERS[i].to_ascii_lowercase()), DNA_INDICES[i]);
+            assert_eq!(alphabet.i2c(DNA_INDICES[i]), DNA_CHARACTERS[i]);
+        }
+    }
+
+    #[test]
+    fn test_custom_alphabet_specification() {
+        let alphabet = CustomAlphabet::from_specification("ACGT U=T", true).unwrap();
+
+        assert_eq!(alphabet.len(), 4);
+        assert_eq!(alphabet.c2i(b'U'), 3);
+        assert_eq!(alphabet.c2i(b'u'), 3);
+        assert_eq!(alphabet.i2c(3), b'T');
+    }
+
+    #[test]
+    fn test_custom_alphabet_invalid_specification() {
+        assert!(CustomAlphabet::new(b"", false).is_err());
+        assert!(CustomAlphabet::new(b"ACGA", false).is_err());
+        assert!(CustomAlphabet::new(b"ACGa", true).is_err());
+        assert!(CustomAlphabet::from_specification("ACGT U=N", false).is_err());
+        assert!(CustomAlphabet::from_specification("ACGT U", false).is_err());
+        assert!(CustomAlphabet::from_specification("ACGT A=C", false).is_err());
+
+        // The last index is reserved for separators and sentinels
+        let characters: Vec<AlphabetChar> = (0 ..= 255).collect();
+        assert!(CustomAlphabet::new(&characters[.. 255], false).is_ok());
+        assert!(CustomAlphabet::new(&characters, false).is_err());
+    }
+
+    #[test]
+    fn test_alphabet_string_from_alphabet() {
+        let alphabet = CustomAlphabet::new(b"TGCA", false).unwrap();
+
+        let string = AlphabetString::from_alphabet("ACGT", alphabet);
+        assert_eq!(*string, vec![3, 2, 1, 0]);
+
+        let pattern = AlphabetPattern::from(string);
+        assert_eq!(pattern.len(), 4);
+        assert_eq!(pattern[0], 3);
+    }
+
+    #[test]
+    fn test_dna_alphabet_try_c2i() {
+        let alphabet = DNAAlphabet::default();
+
+        for i in 0 .. DNA_CHARACTERS.len() {
+            assert_eq!(alphabet.try_c2i(DNA_CHARACTERS[i]), Some(DNA_INDICES[i]));
+        }
+
+        assert_eq!(alphabet.try_c2i(b'N'), None);
+        assert_eq!(alphabet.try_c2i(b'a'), None);
+    }
+
+    #[test]
+    fn test_alphabet_string_try_from_str() {
+        let string = AlphabetString::<DNAAlphabet>::try_from_str("ACGT").unwrap();
+        assert_eq!(*string, DNA_INDICES.to_vec());
+
+        match AlphabetString::<DNAAlphabet>::try_from_str("ACGNT") {
+            Err(e) => match e.kind() {
+                ErrorKind::InvalidCharacter(position, c) => {
+                    assert_eq!(*position, 3);
+                    assert_eq!(*c, b'N');
+                }
+                _ => panic!("Unexpected error: {}", e)
+            },
+            Ok(_) => panic!("'N' should not be accepted")
+        }
+
+        let string: AlphabetString<DNAAlphabet> = "ACGT".parse().unwrap();
+        assert_eq!(*string, DNA_INDICES.to_vec());
+        assert!("ACGNT".parse::<AlphabetString<DNAAlphabet>>().is_err());
+
+        let string = AlphabetString::<DNAAlphabet>::try_from("ACGT".to_string()).unwrap();
+        assert_eq!(*string, DNA_INDICES.to_vec());
+        assert!(AlphabetString::<DNAAlphabet>::try_from("ACGNT".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_alphabet_pattern_try_from_str() {
+        let pattern = AlphabetPattern::<DNAAlphabet>::try_new("ACGT", Direction::BACKWARD);
+        assert_eq!(pattern.unwrap()[0], 3);
+
+        assert!(AlphabetPattern::<DNAAlphabet>::try_from_str("acgt").is_err());
+
+        let pattern: AlphabetPattern<DNAAlphabet> = "ACGT".parse().unwrap();
+        assert_eq!(pattern[0], 0);
+        assert!("acgt".parse::<AlphabetPattern<DNAAlphabet>>().is_err());
+        assert!(AlphabetPattern::<DNAAlphabet>::try_from("ACGT".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_reject() {
+        let alphabet = DNAAlphabet::default();
+
+        let strings =
+            AlphabetString::parse("ACGT", alphabet.clone(), InvalidCharacterPolicy::Reject)
+                .unwrap();
+        assert_eq!(strings.len(), 1);
+        assert_eq!(*strings[0], vec![0, 1, 2, 3]);
+
+        assert!(AlphabetString::parse("ACNT", alphabet, InvalidCharacterPolicy::Reject).is_err());
+    }
+
+    #[test]
+    fn test_parse_skip() {
+        let strings =
+            AlphabetString::parse("NACNNGTN", DNAAlphabet::default(), InvalidCharacterPolicy::Skip)
+                .unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(*strings[0], vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_parse_replace() {
+        let alphabet = DNAAlphabet::default();
+
+        let strings =
+            AlphabetString::parse("ACNT", alphabet.clone(), InvalidCharacterPolicy::Replace(b'A'))
+                .unwrap();
+        assert_eq!(strings.len(), 1);
+        assert_eq!(*strings[0], vec![0, 1, 0, 3]);
+
+        assert!(
+            AlphabetString::parse("ACNT", alphabet, InvalidCharacterPolicy::Replace(b'N')).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_split() {
+        let strings = AlphabetString::parse(
+            "NACNNGTN",
+            DNAAlphabet::default(),
+            InvalidCharacterPolicy::Split
+        )
+        .unwrap();
+
+        assert_eq!(strings.len(), 2);
+        assert_eq!(*strings[0], vec![0, 1]);
+        assert_eq!(*strings[1], vec![2, 3]);
+
+        let strings =
+            AlphabetString::parse("", DNAAlphabet::default(), InvalidCharacterPolicy::Split)
+                .unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].len(), 0);
+
+        let strings =
+            AlphabetString::parse("NN-N", DNAAlphabet::default(), InvalidCharacterPolicy::Split)
+                .unwrap();
+
+        assert!(strings.is_empty());
+    }
+
+    #[test]
+    fn test_dna_alphabet_expand() {
+        let alphabet = DNAAlphabet::default();
+
+        for i in 0 .. DNA_CHARACTERS.len() {
+            assert_eq!(alphabet.expand(DNA_CHARACTERS[i]), Some(vec![DNA_INDICES[i]]));
+        }
+
+        assert_eq!(alphabet.expand(b'R'), Some(vec![0, 2]));
+        assert_eq!(alphabet.expand(b'Y'), Some(vec![1, 3]));
+        assert_eq!(alphabet.expand(b'N'), Some(vec![0, 1, 2, 3]));
+        assert_eq!(alphabet.expand(b'X'), None);
+    }
+
+    #[test]
+    fn test_protein_alphabet_expand() {
+        let alphabet = ProteinAlphabet::default();
+
+        assert_eq!(alphabet.expand(b'C'), Some(vec![1]));
+        assert_eq!(alphabet.expand(b'X'), None);
+    }
+
+    #[test]
+    fn test_ambiguous_pattern() {
+        let mut pattern = AmbiguousPattern::<DNAAlphabet>::from("ARN");
+
+        assert_eq!(pattern.len(), 3);
+        assert!(!pattern.is_empty());
+        assert_eq!(pattern[0], vec![0]);
+        assert_eq!(pattern[1], vec![0, 2]);
+        assert_eq!(pattern[2], vec![0, 1, 2, 3]);
+
+        pattern.set_direction(Direction::BACKWARD);
+
+        assert_eq!(pattern[0], vec![0, 1, 2, 3]);
+        assert_eq!(pattern[2], vec![0]);
+    }
+
+    #[test]
+    fn test_ambiguous_pattern_invalid() {
+        match AmbiguousPattern::try_from_alphabet("ARXN", DNAAlphabet::default()) {
+            Err(e) => match e.kind() {
+                ErrorKind::InvalidCharacter(position, c) => {
+                    assert_eq!(*position, 2);
+                    assert_eq!(*c, b'X');
+                }
+                _ => panic!("Unexpected error: {}", e)
+            },
+            Ok(_) => panic!("'X' should not be accepted")
+        }
+    }
+
+    #[test]
+    fn test_packed_alphabet_string_get() {
+        let string = AlphabetString::<DNAAlphabet>::from("ACGTTGCAACGTTGCAACGTTGCAACGTTGCAACGT");
+        let packed = PackedAlphabetString::from(&string);
+
+        assert_eq!(packed.len(), string.len());
+        assert!(!packed.is_empty());
+        assert!(PackedAlphabetString::<DNAAlphabet>::new(0).is_empty());
+
+        for i in 0 .. string.len() {
+            assert_eq!(packed.get(i), string[i]);
+        }
+    }
+
+    #[test]
+    fn test_packed_alphabet_string_set() {
+        let mut packed = PackedAlphabetString::<ProteinAlphabet>::new(100);
+
+        for i in 0 .. 100 {
+            packed.set(i, (i % 20) as AlphabetIndex);
+        }
+
+        for i in 0 .. 100 {
+            assert_eq!(packed.get(i), (i % 20) as AlphabetIndex);
+        }
+
+        // Overwriting a character leaves its neighbours untouched
+        packed.set(50, 19);
+        packed.set(51, 0);
+
+        assert_eq!(packed.get(49), 9);
+        assert_eq!(packed.get(50), 19);
+        assert_eq!(packed.get(51), 0);
+        assert_eq!(packed.get(52), 12);
+    }
+
+    #[test]
+    fn test_packed_alphabet_string_iter() {
+        let alphabet = CustomAlphabet::new(b"01", false).unwrap();
+        let string = AlphabetString::from_alphabet(&"0110".repeat(50), alphabet);
+        let packed = PackedAlphabetString::from(&string);
+
+        assert_eq!(packed.iter().collect::<Vec<AlphabetIndex>>(), *string);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_packed_alphabet_string_out_of_bounds() {
+        PackedAlphabetString::<DNAAlphabet>::from("ACGT").get(4);
+    }
+
+    #[test]
+    fn test_dna_alphabet_complement() {
+        let alphabet = DNAAlphabet::default();
+
+        assert_eq!(alphabet.complement(alphabet.c2i(b'A')), Some(alphabet.c2i(b'T')));
+        assert_eq!(alphabet.complement(alphabet.c2i(b'C')), Some(alphabet.c2i(b'G')));
+        assert_eq!(alphabet.complement(alphabet.c2i(b'G')), Some(alphabet.c2i(b'C')));
+        assert_eq!(alphabet.complement(alphabet.c2i(b'T')), Some(alphabet.c2i(b'A')));
+    }
+
+    #[test]
+    fn test_protein_alphabet_complement() {
+        assert_eq!(ProteinAlphabet::default().complement(0), None);
+    }
+
+    #[test]
+    fn test_alphabet_string_reverse_complement() {
+        let string = AlphabetString::<DNAAlphabet>::from("AACGTTTC");
+
+        assert_eq!(
+            *string.reverse_complement().unwrap(),
+            *AlphabetString::<DNAAlphabet>::from("GAAACGTT")
+        );
+
+        assert!(AlphabetString::<ProteinAlphabet>::from("ACD")
+            .reverse_complement()
+            .is_none());
+    }
+
+    #[test]
+    fn test_alphabet_pattern_reverse_complement() {
+        let pattern = AlphabetPattern::<DNAAlphabet>::new("AACG", Direction::BACKWARD);
+        let reverse_complement = pattern.reverse_complement().unwrap();
+
+        assert_eq!(reverse_complement.len(), 4);
+        assert_eq!(*reverse_complement.direction(), Direction::BACKWARD);
+
+        // CGTT read backwards
+        assert_eq!(reverse_complement[0], 3);
+        assert_eq!(reverse_complement[1], 3);
+        assert_eq!(reverse_complement[2], 2);
+        assert_eq!(reverse_complement[3], 1);
+    }
+
+    #[test]
+    fn test_separated_alphabet() {
+        let alphabet = SeparatedAlphabet::new(DNAAlphabet::default());
+
+        for i in 0 .. DNA_CHARACTERS.len() {
+            assert_eq!(alphabet.c2i(DNA_CHARACTERS[i]), DNA_INDICES[i]);
+            assert_eq!(alphabet.i2c(DNA_INDICES[i]), DNA_CHARACTERS[i]);
+        }
+
+        assert_eq!(alphabet.separator(), 4);
+        assert_eq!(alphabet.i2c(4), b'$');
+        assert_eq!(alphabet.try_c2i(b'$'), None);
+        assert_eq!(alphabet.complement(0), Some(3));
+        assert_eq!(alphabet.complement(4), None);
+    }
+
+    #[test]
+    fn test_separated_alphabet_len() {
+        assert_eq!(SeparatedAlphabet::new(DNAAlphabet::default()).len(), 5);
+        assert_eq!(SeparatedAlphabet::new(ProteinAlphabet::default()).len(), 21);
+    }
+
+    #[test]
+    fn test_separated_alphabet_bits() {
+        assert_eq!(SeparatedAlphabet::new(DNAAlphabet::default()).bits(), 3);
+        assert_eq!(SeparatedAlphabet::new(ProteinAlphabet::default()).bits(), 5);
+        assert_eq!(SeparatedAlphabet::new(CustomAlphabet::new(b"ACG", false).unwrap()).bits(), 2);
+    }
 }
@@ -9,19 +9,26 @@ use serde::{
     Serialize
 };
 
-use crate::alphabet::{
-    Alphabet,
-    AlphabetString
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetIndex,
+        PackedAlphabetString
+    },
+    parallel
 };
 
 const ULL1: u64 = 1;
 
+/// Amount of set or unset bits between two select samples
+const SELECT_SAMPLE_RATE: usize = 4096;
+
 // ======================================================================
 // == Bitvec
 // ======================================================================
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
-/// Bitvector with Jacobson’s rank
+/// Bitvector with Jacobson’s rank and sampled select
 pub struct Bitvec {
     /// Size of the bitvector
     n: usize,
@@ -30,23 +37,61 @@ pub struct Bitvec {
     bitvector: Vec<u64>,
 
     /// Interleaved first and second level counts
-    counts: Vec<usize>
+    counts: Vec<usize>,
+
+    /// Block of 8 words that holds every `SELECT_SAMPLE_RATE`th set bit
+    select1_samples: Vec<usize>,
+
+    /// Block of 8 words that holds every `SELECT_SAMPLE_RATE`th unset bit
+    select0_samples: Vec<usize>
 }
 
 impl Bitvec {
-    /// Create a new bitvector
+    /// Create a new bitvector, with a word after the last bit so the rank of every position up
+    /// to and including the length can be asked
     pub fn new(n: usize) -> Self {
-        let bitvector = vec![0; (n + 63) / 64];
-        let counts = vec![0; (n + 7) / 4];
+        let bitvector = vec![0; n / 64 + 1];
+        let counts = vec![0; bitvector.len().div_ceil(8) * 2];
         Bitvec {
             n,
             bitvector,
-            counts
+            counts,
+            select1_samples: vec![],
+            select0_samples: vec![]
+        }
+    }
+
+    /// Concatenate bitvectors, every bitvector but the last one must fill its last word. The
+    /// counts still have to be calculated.
+    pub fn concat(parts: Vec<Bitvec>) -> Self {
+        let mut bitvector = Vec::with_capacity(parts.iter().map(|bv| bv.bitvector.len()).sum());
+
+        let mut n = 0;
+        for mut part in parts {
+            assert!(n % 64 == 0, "Only bitvectors that fill their last word can be extended!");
+
+            // Drop the empty word after the previous part
+            bitvector.truncate(n / 64);
+
+            n += part.n;
+            bitvector.append(&mut part.bitvector);
+        }
+        bitvector.resize(n / 64 + 1, 0);
+
+        let counts = vec![0; bitvector.len().div_ceil(8) * 2];
+        Bitvec {
+            n,
+            bitvector,
+            counts,
+            select1_samples: vec![],
+            select0_samples: vec![]
         }
     }
 
     /// Index the bitvector by calculating the count levels
     pub fn calculate_counts(&mut self) {
+        self.counts.fill(0);
+
         let mut level1_counts: usize = 0;
         let mut level2_counts: usize = 0;
 
@@ -66,6 +111,34 @@ impl Bitvec {
                 level2_counts += self.bitvector[w].popcnt() as usize;
             }
         }
+
+        self.calculate_select_samples();
+    }
+
+    /// Sample the blocks of every `SELECT_SAMPLE_RATE`th set and unset bit
+    fn calculate_select_samples(&mut self) {
+        self.select1_samples = vec![];
+        self.select0_samples = vec![];
+
+        let mut next_one = SELECT_SAMPLE_RATE;
+        let mut next_zero = SELECT_SAMPLE_RATE;
+
+        for block in 0 .. self.blocks() {
+            // Only the bits before the end of the bitvector count as unset bits
+            let bits = self.n.min(block * 512 + 512);
+            let ones = self.rank(bits);
+            let zeros = bits - ones;
+
+            while next_one < ones {
+                self.select1_samples.push(block);
+                next_one += SELECT_SAMPLE_RATE;
+            }
+
+            while next_zero < zeros {
+                self.select0_samples.push(block);
+                next_zero += SELECT_SAMPLE_RATE;
+            }
+        }
     }
 
     /// Check if a bit is set at a given position
@@ -75,6 +148,22 @@ impl Bitvec {
         return (self.bitvector[word] & (ULL1 << bit)) != 0;
     }
 
+    /// Get `len` bits starting at a position, with the first bit as the least significant bit
+    pub(crate) fn bits(&self, pos: usize, len: usize) -> u64 {
+        let w = pos / 64;
+        let b = pos % 64;
+
+        let mut bits = self.bitvector[w] >> b;
+        if b + len > 64 && w + 1 < self.bitvector.len() {
+            bits |= self.bitvector[w + 1] << (64 - b);
+        }
+
+        if len < 64 {
+            bits &= (ULL1 << len) - 1;
+        }
+        return bits;
+    }
+
     /// Set the bit at a position to a different value
     pub fn set(&mut self, pos: usize, value: bool) {
         let w: usize = pos / 64;
@@ -94,11 +183,98 @@ impl Bitvec {
         return l1c + l2c + self.level3_counts(pos / 64, pos % 64);
     }
 
+    /// Get the number of unset bits in the range 0 to pos
+    pub fn rank0(&self, pos: usize) -> usize {
+        return pos - self.rank(pos);
+    }
+
+    /// Get the position of the `k`th set bit, counting from 0
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.rank(self.n) {
+            return None;
+        }
+
+        return Some(self.select(
+            k,
+            &self.select1_samples,
+            |block| self.counts[block * 2],
+            |word| word
+        ));
+    }
+
+    /// Get the position of the `k`th unset bit, counting from 0
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.rank0(self.n) {
+            return None;
+        }
+
+        return Some(self.select(
+            k,
+            &self.select0_samples,
+            |block| block * 512 - self.counts[block * 2],
+            |word| !word
+        ));
+    }
+
+    /// Find the `k`th bit of a kind, given the samples and the counts of that kind
+    fn select<F, G>(&self, mut k: usize, samples: &[usize], block_counts: F, word_bits: G) -> usize
+    where
+        F: Fn(usize) -> usize,
+        G: Fn(u64) -> u64
+    {
+        // The samples narrow down the blocks that can hold the bit
+        let s = k / SELECT_SAMPLE_RATE;
+        let mut first = if s == 0 { 0 } else { samples[s - 1] };
+        let mut last = samples.get(s).copied().unwrap_or(self.blocks() - 1);
+
+        // Binary search for the last block with at most k bits before it
+        while first < last {
+            let middle = (first + last).div_ceil(2);
+            if block_counts(middle) <= k {
+                first = middle;
+            } else {
+                last = middle - 1;
+            }
+        }
+
+        k -= block_counts(first);
+
+        // Scan the words of the block
+        let mut w = first * 8;
+        loop {
+            let mut word = word_bits(self.bitvector[w]);
+            let ones = word.popcnt() as usize;
+
+            if k < ones {
+                for _ in 0 .. k {
+                    word &= word - 1;
+                }
+
+                return w * 64 + word.trailing_zeros() as usize;
+            }
+
+            k -= ones;
+            w += 1;
+        }
+    }
+
+    /// Get the amount of blocks of 8 words
+    fn blocks(&self) -> usize {
+        return self.bitvector.len().div_ceil(8);
+    }
+
     /// Get the length of the bitvector
     pub fn len(&self) -> usize {
         return self.n;
     }
 
+    /// Get the amount of bytes used by the bits, the counts and the select samples
+    pub fn size_in_bytes(&self) -> usize {
+        return self.bitvector.len() * std::mem::size_of::<u64>()
+            + (self.counts.len() + self.select1_samples.len() + self.select0_samples.len())
+                * std::mem::size_of::<usize>();
+    }
+
     /// Get the level 1 counts
     fn level1_counts(&self, w: usize) -> usize {
         return self.counts[(w / 8) * 2];
@@ -137,59 +313,331 @@ impl fmt::Debug for Bitvec {
     }
 }
 
+// ======================================================================
+// == RankSelect
+// ======================================================================
+
+/// Bitvector that supports rank and select queries
+pub trait RankSelect: Send + Sync {
+    /// Build the structure from a plain bitvector, of which the counts do not have to be
+    /// calculated yet
+    fn from_bitvec(bitvector: Bitvec) -> Self;
+
+    /// Get the length of the bitvector
+    fn len(&self) -> usize;
+
+    /// Check whether the bitvector is empty
+    fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Check if a bit is set at a given position
+    fn get(&self, pos: usize) -> bool;
+
+    /// Get the number of set bits in the range 0 to pos
+    fn rank(&self, pos: usize) -> usize;
+
+    /// Get the number of unset bits in the range 0 to pos
+    fn rank0(&self, pos: usize) -> usize {
+        return pos - self.rank(pos);
+    }
+
+    /// Get the position of the `k`th set bit, counting from 0
+    fn select1(&self, k: usize) -> Option<usize>;
+
+    /// Get the position of the `k`th unset bit, counting from 0
+    fn select0(&self, k: usize) -> Option<usize>;
+
+    /// Get the amount of bytes used by the structure
+    fn size_in_bytes(&self) -> usize;
+}
+
+impl RankSelect for Bitvec {
+    fn from_bitvec(mut bitvector: Bitvec) -> Self {
+        bitvector.calculate_counts();
+        return bitvector;
+    }
+
+    fn len(&self) -> usize {
+        return Bitvec::len(self);
+    }
+
+    fn get(&self, pos: usize) -> bool {
+        return Bitvec::get(self, pos);
+    }
+
+    fn rank(&self, pos: usize) -> usize {
+        return Bitvec::rank(self, pos);
+    }
+
+    fn select1(&self, k: usize) -> Option<usize> {
+        return Bitvec::select1(self, k);
+    }
+
+    fn select0(&self, k: usize) -> Option<usize> {
+        return Bitvec::select0(self, k);
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        return Bitvec::size_in_bytes(self);
+    }
+}
+
+// ======================================================================
+// == Occurences
+// ======================================================================
+
+/// Rank structure over a BWT that counts the occurences of characters before a position
+pub trait Occurences: Send + Sync {
+    /// Build the structure for a BWT with the sentinel at a given position
+    fn from_bwt<A: Alphabet>(bwt: &PackedAlphabetString<A>, sentinel: usize) -> Self;
+
+    /// Get the amount of occurences of a character before a position
+    fn occ(&self, char_i: usize, i: usize) -> usize;
+
+    /// Get the amount of characters smaller than a character before a position, the sentinel
+    /// included
+    fn cumulative_occ(&self, char_i: usize, i: usize) -> usize;
+
+    /// Get the character at a position of the BWT, the sentinel is reported as character 0
+    fn symbol(&self, i: usize) -> AlphabetIndex;
+
+    /// Get the position of the sentinel in the BWT
+    fn sentinel(&self) -> usize;
+
+    /// Get the amount of bytes used by the structure
+    fn size_in_bytes(&self) -> usize;
+}
+
 // ======================================================================
 // == OccurenceTable
 // ======================================================================
 
-// TODO: interleave bitvectors to prevent an inefficient vector of vectors
+/// Occurence table with a cumulative bitvector of type `B` for every character
 #[derive(Serialize, Deserialize, Debug)]
-pub struct OccurenceTable {
+pub struct OccurenceTable<B: RankSelect = Bitvec> {
     // TODO: make array? because 2D vec now?
-    table: Vec<Bitvec>,
+    table: Vec<B>,
 
     /// Position of the sentinel character
     pub sentinel: usize
 }
 
-impl OccurenceTable {
-    pub fn from_bwt<A: Alphabet>(bwt: &AlphabetString<A>, sentinel: usize) -> Self {
+impl<B: RankSelect> Occurences for OccurenceTable<B> {
+    fn from_bwt<A: Alphabet>(bwt: &PackedAlphabetString<A>, sentinel: usize) -> Self {
         let alphabet_length = bwt.alphabet.len();
 
-        let mut table = vec![Bitvec::new(bwt.len()); alphabet_length];
+        // Fill the bitvectors of all characters in a single pass over every chunk of the BWT
+        let mut parts = parallel::map_chunks(bwt.len(), 64, |chunk| {
+            let mut bitvectors = vec![Bitvec::new(chunk.len()); alphabet_length];
 
-        // TODO compare if to .filter()
-        bwt.iter().enumerate().for_each(|(i, char_i)| {
-            if i != sentinel {
-                for j in (*char_i) as usize .. alphabet_length {
-                    table[j].set(i, true);
+            for i in chunk.clone() {
+                if i != sentinel {
+                    for bv in bitvectors[bwt.get(i) as usize ..].iter_mut() {
+                        bv.set(i - chunk.start, true);
+                    }
                 }
             }
+
+            return bitvectors;
         });
 
-        // Calculate the counts to allow efficient rank operations
-        for i in 0 .. alphabet_length {
-            table[i].calculate_counts();
+        // An empty BWT still needs a bitvector per character
+        if parts.is_empty() {
+            parts.push(vec![Bitvec::new(0); alphabet_length]);
         }
 
+        let mut columns: Vec<Vec<Bitvec>> = vec![Vec::with_capacity(parts.len()); alphabet_length];
+        for part in parts {
+            for (column, bv) in columns.iter_mut().zip(part) {
+                column.push(bv);
+            }
+        }
+
+        // Calculate the counts to allow efficient rank operations
+        let table = columns
+            .into_iter()
+            .map(|column| B::from_bitvec(Bitvec::concat(column)))
+            .collect();
+
         Self {
             table,
             sentinel
         }
     }
 
-    pub fn occ(&self, char_i: usize, i: usize) -> usize {
+    fn occ(&self, char_i: usize, i: usize) -> usize {
         if char_i == 0 {
             return self.table[char_i].rank(i);
         }
         return self.table[char_i].rank(i) - self.table[char_i - 1].rank(i);
     }
 
-    pub fn cumulative_occ(&self, char_i: usize, i: usize) -> usize {
+    fn cumulative_occ(&self, char_i: usize, i: usize) -> usize {
         if char_i == 0 {
             return (self.sentinel < i) as usize;
         }
         return self.table[char_i - 1].rank(i) + (self.sentinel < i) as usize;
     }
+
+    fn symbol(&self, i: usize) -> AlphabetIndex {
+        if i == self.sentinel {
+            return 0;
+        }
+
+        // The bit of a position is set for its character and all larger characters
+        return self.table.partition_point(|bv| !bv.get(i)) as AlphabetIndex;
+    }
+
+    fn sentinel(&self) -> usize {
+        return self.sentinel;
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        return self.table.iter().map(|bv| bv.size_in_bytes()).sum();
+    }
+}
+
+// ======================================================================
+// == InterleavedOccurenceTable
+// ======================================================================
+
+/// A cache line of words
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[repr(C, align(64))]
+struct CacheLine([u64; 8]);
+
+/// Occurence table that interleaves the counts and the bits of all characters, so a rank query
+/// only touches the block of its position. For DNA a block fits in a single cache line.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InterleavedOccurenceTable {
+    /// Amount of characters in the alphabet
+    alphabet_length: usize,
+
+    /// Blocks of 64 positions: for every character the amount of characters smaller than or
+    /// equal to it before the block, followed by the cumulative bits of every character
+    blocks: Vec<CacheLine>,
+
+    /// Position of the sentinel character
+    pub sentinel: usize
+}
+
+impl InterleavedOccurenceTable {
+    /// Get the amount of cache lines per block
+    fn lines_per_block(alphabet_length: usize) -> usize {
+        return (2 * alphabet_length).div_ceil(8);
+    }
+
+    /// Get a word of a block
+    fn word(&self, block: usize, w: usize) -> u64 {
+        let line = block * Self::lines_per_block(self.alphabet_length) + w / 8;
+        return self.blocks[line].0[w % 8];
+    }
+
+    /// Get the amount of characters smaller than or equal to a character before a position
+    fn rank(&self, char_i: usize, i: usize) -> usize {
+        let (block, b) = (i / 64, i % 64);
+
+        let count = self.word(block, char_i) as usize;
+        let bits = self.word(block, self.alphabet_length + char_i) & ((ULL1 << b) - 1);
+
+        return count + bits.popcnt() as usize;
+    }
+}
+
+impl Occurences for InterleavedOccurenceTable {
+    fn from_bwt<A: Alphabet>(bwt: &PackedAlphabetString<A>, sentinel: usize) -> Self {
+        let alphabet_length = bwt.alphabet.len();
+        let lines_per_block = Self::lines_per_block(alphabet_length);
+
+        // One more block than needed, so the position after the BWT has a block
+        let n_blocks = bwt.len() / 64 + 1;
+
+        // Fill the blocks per chunk, with counts relative to the start of the chunk
+        let parts = parallel::map_chunks(n_blocks, 1, |chunk| {
+            let mut lines = vec![CacheLine([0; 8]); chunk.len() * lines_per_block];
+            let mut counts = vec![0; alphabet_length];
+
+            for (block, lines) in chunk.zip(lines.chunks_mut(lines_per_block)) {
+                let mut words = vec![0; 2 * alphabet_length];
+                words[.. alphabet_length].copy_from_slice(&counts);
+
+                for i in block * 64 .. bwt.len().min(block * 64 + 64) {
+                    if i != sentinel {
+                        for j in bwt.get(i) as usize .. alphabet_length {
+                            words[alphabet_length + j] |= ULL1 << (i % 64);
+                        }
+                    }
+                }
+
+                for j in 0 .. alphabet_length {
+                    counts[j] += words[alphabet_length + j].popcnt();
+                }
+
+                for (w, word) in words.into_iter().enumerate() {
+                    lines[w / 8].0[w % 8] = word;
+                }
+            }
+
+            return (lines, counts);
+        });
+
+        // Make the counts absolute by adding the counts of the previous chunks
+        let mut blocks = Vec::with_capacity(n_blocks * lines_per_block);
+        let mut offsets = vec![0; alphabet_length];
+        for (mut lines, counts) in parts {
+            for block in lines.chunks_mut(lines_per_block) {
+                for j in 0 .. alphabet_length {
+                    block[j / 8].0[j % 8] += offsets[j];
+                }
+            }
+
+            for j in 0 .. alphabet_length {
+                offsets[j] += counts[j];
+            }
+
+            blocks.extend(lines);
+        }
+
+        return Self {
+            alphabet_length: alphabet_length,
+            blocks:          blocks,
+            sentinel:        sentinel
+        };
+    }
+
+    fn occ(&self, char_i: usize, i: usize) -> usize {
+        if char_i == 0 {
+            return self.rank(char_i, i);
+        }
+        return self.rank(char_i, i) - self.rank(char_i - 1, i);
+    }
+
+    fn cumulative_occ(&self, char_i: usize, i: usize) -> usize {
+        if char_i == 0 {
+            return (self.sentinel < i) as usize;
+        }
+        return self.rank(char_i - 1, i) + (self.sentinel < i) as usize;
+    }
+
+    fn symbol(&self, i: usize) -> AlphabetIndex {
+        if i == self.sentinel {
+            return 0;
+        }
+
+        let (block, b) = (i / 64, i % 64);
+        return (0 .. self.alphabet_length)
+            .find(|j| self.word(block, self.alphabet_length + j) & (ULL1 << b) != 0)
+            .unwrap_or(0) as AlphabetIndex;
+    }
+
+    fn sentinel(&self) -> usize {
+        return self.sentinel;
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        return self.blocks.len() * std::mem::size_of::<CacheLine>();
+    }
 }
 
 // ======================================================================
@@ -198,17 +646,28 @@ impl OccurenceTable {
 
 #[cfg(test)]
 mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
     use crate::{
         alphabet::{
             Alphabet,
             AlphabetIndex,
-            AlphabetString,
-            DNAAlphabet
+            DNAAlphabet,
+            PackedAlphabetString,
+            ProteinAlphabet
         },
         bitvector::{
             Bitvec,
-            OccurenceTable
-        }
+            InterleavedOccurenceTable,
+            OccurenceTable,
+            Occurences
+        },
+        parallel,
+        testing::random_text
     };
 
     const BITVEC_SIZE: usize = 10_000;
@@ -218,6 +677,9 @@ mod tests {
         [2, 1, 0, 1, 0, 0, 3, 0, 3, 2, 0, 0, 1, 2, 2, 0, 3, 1, 3, 0, 2];
     const SENTINEL_POS: usize = 2;
 
+    const PROTEIN_BWT: &str = "ESRSTLVLRTFHMLFSARSFSKIYFVDGWKA";
+    const PROTEIN_SENTINEL_POS: usize = 16;
+
     #[test]
     fn test_index_operator_empty() {
         let bitvector = Bitvec::new(BITVEC_SIZE);
@@ -259,12 +721,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rank0() {
+        let mut bitvector = Bitvec::new(BITVEC_SIZE);
+
+        for i in (0 .. BITVEC_SIZE).step_by(3) {
+            bitvector.set(i, true);
+        }
+
+        bitvector.calculate_counts();
+
+        for i in 0 ..= BITVEC_SIZE {
+            assert_eq!(bitvector.rank0(i), i - i.div_ceil(3));
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let mut rng = StdRng::seed_from_u64(15);
+
+        // Sparse, dense and balanced bitvectors, some spanning several select samples
+        for (n, density) in [(1, 0.5), (64, 0.5), (1000, 0.01), (50_000, 0.5), (50_000, 0.99)] {
+            let mut bitvector = Bitvec::new(n);
+
+            let mut ones = vec![];
+            let mut zeros = vec![];
+            for i in 0 .. n {
+                if rng.gen_bool(density) {
+                    bitvector.set(i, true);
+                    ones.push(i);
+                } else {
+                    zeros.push(i);
+                }
+            }
+
+            bitvector.calculate_counts();
+
+            for (k, i) in ones.iter().enumerate() {
+                assert_eq!(bitvector.select1(k), Some(*i));
+            }
+            for (k, i) in zeros.iter().enumerate() {
+                assert_eq!(bitvector.select0(k), Some(*i));
+            }
+
+            assert_eq!(bitvector.select1(ones.len()), None);
+            assert_eq!(bitvector.select0(zeros.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_select_full() {
+        let mut bitvector = Bitvec::new(BITVEC_SIZE);
+
+        for i in 0 .. BITVEC_SIZE {
+            bitvector.set(i, true);
+        }
+
+        bitvector.calculate_counts();
+
+        for k in 0 .. BITVEC_SIZE {
+            assert_eq!(bitvector.select1(k), Some(k));
+        }
+
+        assert_eq!(bitvector.select1(BITVEC_SIZE), None);
+        assert_eq!(bitvector.select0(0), None);
+    }
+
     #[test]
     fn test_initialize_occurence_table() {
         let alphabet = DNAAlphabet::default();
 
-        let occurence_table =
-            OccurenceTable::from_bwt(&AlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
+        let occurence_table: OccurenceTable =
+            OccurenceTable::from_bwt(&PackedAlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
 
         let mut result = vec![Bitvec::new(21); alphabet.len()];
         for i in 0 .. BWT_INDEX_VEC.len() {
@@ -280,10 +808,24 @@ mod tests {
         assert_eq!(occurence_table.table, result);
     }
 
+    #[test]
+    fn test_symbol() {
+        let occurence_table: OccurenceTable =
+            OccurenceTable::from_bwt(&PackedAlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
+
+        for i in 0 .. BWT_INDEX_VEC.len() {
+            if i == SENTINEL_POS {
+                assert_eq!(occurence_table.symbol(i), 0);
+            } else {
+                assert_eq!(occurence_table.symbol(i), BWT_INDEX_VEC[i]);
+            }
+        }
+    }
+
     #[test]
     fn test_occ() {
-        let occurence_table =
-            OccurenceTable::from_bwt(&AlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
+        let occurence_table: OccurenceTable =
+            OccurenceTable::from_bwt(&PackedAlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
 
         let occ_results: Vec<Vec<usize>> = vec![
             vec![0, 0, 0, 0, 0, 1, 2, 2, 3, 3, 3, 4, 5, 5, 5, 5, 6, 6, 6, 6, 7],
@@ -301,8 +843,8 @@ mod tests {
 
     #[test]
     fn test_cumulative_occ() {
-        let occurence_table =
-            OccurenceTable::from_bwt(&AlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
+        let occurence_table: OccurenceTable =
+            OccurenceTable::from_bwt(&PackedAlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
 
         let occ_results: Vec<Vec<usize>> = vec![
             vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
@@ -317,4 +859,116 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_occ_protein() {
+        let alphabet = ProteinAlphabet::default();
+        let bwt = PackedAlphabetString::<ProteinAlphabet>::from(PROTEIN_BWT);
+
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(&bwt, PROTEIN_SENTINEL_POS);
+
+        let mut occ_results = vec![0; alphabet.len()];
+        for i in 0 ..= bwt.len() {
+            for j in 0 .. alphabet.len() {
+                assert_eq!(occurence_table.occ(j, i), occ_results[j]);
+            }
+
+            if i < bwt.len() && i != PROTEIN_SENTINEL_POS {
+                occ_results[bwt.get(i) as usize] += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_cumulative_occ_protein() {
+        let alphabet = ProteinAlphabet::default();
+        let bwt = PackedAlphabetString::<ProteinAlphabet>::from(PROTEIN_BWT);
+
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(&bwt, PROTEIN_SENTINEL_POS);
+
+        for i in 0 ..= bwt.len() {
+            for j in 0 .. alphabet.len() {
+                let smaller = (0 .. i)
+                    .filter(|k| *k == PROTEIN_SENTINEL_POS || (bwt.get(*k) as usize) < j)
+                    .count();
+
+                assert_eq!(occurence_table.cumulative_occ(j, i), smaller);
+            }
+        }
+    }
+
+    #[test]
+    fn test_occ_threads() {
+        let mut rng = StdRng::seed_from_u64(10);
+
+        let mut bwt =
+            PackedAlphabetString::<DNAAlphabet>::with_alphabet(1_000, DNAAlphabet::default());
+        for i in 0 .. bwt.len() {
+            bwt.set(i, rng.gen_range(0 .. 4));
+        }
+
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(&bwt, 500);
+
+        for threads in [2, 3] {
+            let parallel_table: OccurenceTable =
+                parallel::install(threads, || OccurenceTable::from_bwt(&bwt, 500));
+
+            for i in 0 ..= bwt.len() {
+                for j in 0 .. bwt.alphabet.len() {
+                    assert_eq!(parallel_table.occ(j, i), occurence_table.occ(j, i));
+                }
+            }
+        }
+
+        let empty = PackedAlphabetString::<DNAAlphabet>::with_alphabet(0, DNAAlphabet::default());
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(&empty, 0);
+        assert_eq!(occurence_table.occ(3, 0), 0);
+    }
+
+    fn assert_same_occurences<A: Alphabet>(bwt: &PackedAlphabetString<A>, sentinel: usize) {
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(bwt, sentinel);
+        let interleaved = InterleavedOccurenceTable::from_bwt(bwt, sentinel);
+
+        assert_eq!(interleaved.sentinel(), sentinel);
+
+        for i in 0 .. bwt.len() {
+            assert_eq!(interleaved.symbol(i), occurence_table.symbol(i));
+        }
+
+        for i in 0 ..= bwt.len() {
+            for j in 0 .. bwt.alphabet.len() {
+                assert_eq!(interleaved.occ(j, i), occurence_table.occ(j, i));
+                assert_eq!(interleaved.cumulative_occ(j, i), occurence_table.cumulative_occ(j, i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interleaved_occ() {
+        assert_same_occurences(&PackedAlphabetString::<DNAAlphabet>::from(BWT), SENTINEL_POS);
+    }
+
+    #[test]
+    fn test_interleaved_occ_protein() {
+        assert_same_occurences(
+            &PackedAlphabetString::<ProteinAlphabet>::from(PROTEIN_BWT),
+            PROTEIN_SENTINEL_POS
+        );
+    }
+
+    #[test]
+    fn test_interleaved_occ_random() {
+        let mut rng = StdRng::seed_from_u64(12);
+
+        // Lengths around the block boundaries
+        for length in [63, 64, 65, 128, 1000] {
+            let bwt = random_text(&mut rng, length);
+            let sentinel = rng.gen_range(0 .. length);
+
+            assert_same_occurences(
+                &PackedAlphabetString::<DNAAlphabet>::from(bwt.as_str()),
+                sentinel
+            );
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering
+    }
+};
+
+use crate::errors::{
+    ErrorKind,
+    Result
+};
+
+// ======================================================================
+// == ConstructionOptions
+// ======================================================================
+
+/// Options for the construction of an index
+#[derive(Clone, Debug)]
+pub struct ConstructionOptions {
+    /// Maximum amount of bytes the construction buffers may use, unlimited if `None`. The
+    /// external construction keeps the text and the suffix array on disk, the budget has to fit
+    /// the finished index.
+    pub memory_budget: Option<usize>,
+
+    /// Amount of threads used for the construction, only used with the `parallel` feature
+    pub threads: usize,
+
+    /// Directory for the temporary files of the external construction, the system default if
+    /// `None`
+    pub temp_dir: Option<PathBuf>,
+
+    /// Maximum amount of suffixes the external construction sorts at once, derived from the
+    /// memory budget if `None`
+    pub block_size: Option<usize>,
+
+    /// Leave the original text out of the index, substrings are then extracted from the BWT
+    pub self_index: bool
+}
+
+impl Default for ConstructionOptions {
+    fn default() -> Self {
+        Self {
+            memory_budget: None,
+            threads:       1,
+            temp_dir:      None,
+            block_size:    None,
+            self_index:    false
+        }
+    }
+}
+
+// ======================================================================
+// == ConstructionStats
+// ======================================================================
+
+/// Statistics gathered during the construction of an index
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ConstructionStats {
+    /// The largest amount of bytes in use by the construction buffers at any time
+    pub peak_memory: usize
+}
+
+// ======================================================================
+// == MemoryTracker
+// ======================================================================
+
+/// Bookkeeping of the memory used by the construction buffers, shared by all threads
+///
+/// Only the large buffers (text, suffix array, BWT, tables) are accounted for, small
+/// allocations are negligible in comparison.
+#[derive(Debug)]
+pub struct MemoryTracker {
+    /// Maximum amount of bytes in use at any time
+    budget: Option<usize>,
+
+    /// Amount of bytes currently in use
+    current: AtomicUsize,
+
+    /// Maximum amount of bytes that was in use
+    peak: AtomicUsize
+}
+
+impl MemoryTracker {
+    pub fn new(budget: Option<usize>) -> Self {
+        Self {
+            budget:  budget,
+            current: AtomicUsize::new(0),
+            peak:    AtomicUsize::new(0)
+        }
+    }
+
+    /// Account for a new buffer, fails if this exceeds the budget
+    pub fn allocate(&self, bytes: usize) -> Result<()> {
+        let budget = self.budget.unwrap_or(usize::MAX);
+
+        let update = self
+            .current
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                Some(current + bytes).filter(|required| *required <= budget)
+            });
+
+        return match update {
+            Ok(current) => {
+                self.peak.fetch_max(current + bytes, Ordering::SeqCst);
+                Ok(())
+            }
+            Err(current) => Err(ErrorKind::MemoryBudgetExceeded(current + bytes, budget).into())
+        };
+    }
+
+    /// Account for a buffer that was dropped
+    pub fn free(&self, bytes: usize) {
+        self.current.fetch_sub(bytes, Ordering::SeqCst);
+    }
+
+    /// Get the amount of bytes currently in use
+    pub fn current(&self) -> usize {
+        return self.current.load(Ordering::SeqCst);
+    }
+
+    /// Get the statistics of the construction so far
+    pub fn stats(&self) -> ConstructionStats {
+        return ConstructionStats {
+            peak_memory: self.peak.load(Ordering::SeqCst)
+        };
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        construction::MemoryTracker,
+        errors::ErrorKind
+    };
+
+    #[test]
+    fn test_peak_memory() {
+        let memory = MemoryTracker::new(None);
+
+        memory.allocate(100).unwrap();
+        memory.allocate(50).unwrap();
+        memory.free(100);
+        memory.allocate(20).unwrap();
+
+        assert_eq!(memory.current(), 70);
+        assert_eq!(memory.stats().peak_memory, 150);
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let memory = MemoryTracker::new(Some(100));
+
+        memory.allocate(60).unwrap();
+
+        match memory.allocate(60) {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(120, 100) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+
+        // A failed allocation is not accounted for
+        memory.allocate(40).unwrap();
+        assert_eq!(memory.stats().peak_memory, 100);
+    }
+}
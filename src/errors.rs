@@ -10,4 +10,46 @@ error_chain! {
     foreign_links {
         StdIo(std::io::Error) #[doc = "IO"];
     }
+
+    errors {
+        InvalidAlphabet(reason: String) {
+            description("invalid alphabet")
+            display("invalid alphabet: {}", reason)
+        }
+
+        InvalidCharacter(position: usize, character: u8) {
+            description("invalid character")
+            display("'{}' at position {} is not a part of the alphabet", *character as char, position)
+        }
+
+        MissingComplement {
+            description("missing complement")
+            display("the alphabet does not define complements")
+        }
+
+        MemoryBudgetExceeded(required: usize, budget: usize) {
+            description("memory budget exceeded")
+            display("construction requires {} bytes, but the memory budget is {} bytes", required, budget)
+        }
+
+        TextTooLong(length: usize, max_length: usize) {
+            description("text too long")
+            display("a text of {} characters exceeds the maximum of {} characters for the text position type", length, max_length)
+        }
+
+        InvalidRange(start: usize, length: usize, text_length: usize) {
+            description("invalid range")
+            display("{} characters at position {} exceed the text length of {}", length, start, text_length)
+        }
+
+        InvalidSearchScheme(reason: String) {
+            description("invalid search scheme")
+            display("invalid search scheme: {}", reason)
+        }
+
+        AlignmentFailed(position: usize, distance: usize) {
+            description("alignment failed")
+            display("the pattern does not align at position {} with at most {} edits", position, distance)
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::mem;
+
 use serde::{
     Deserialize,
     Serialize
@@ -6,136 +8,292 @@ use serde::{
 use crate::{
     alphabet::{
         Alphabet,
-        AlphabetIndex,
         AlphabetPattern,
         AlphabetString,
-        Direction
+        AmbiguousPattern,
+        Direction,
+        PackedAlphabetString
+    },
+    bitvector::{
+        Bitvec,
+        OccurenceTable,
+        Occurences,
+        RankSelect
+    },
+    construction::{
+        ConstructionOptions,
+        ConstructionStats,
+        MemoryTracker
+    },
+    errors::{
+        ErrorKind,
+        Result
+    },
+    index::{
+        self,
+        search_scheme::{
+            Distance,
+            Search,
+            SearchScheme
+        },
+        Alignment,
+        AmbiguousMatch,
+        ApproximateMatch,
+        HammingMatch,
+        Locate,
+        Locator,
+        Strand
     },
-    bitvector::OccurenceTable,
+    matrix::BandedMatrix,
+    parallel,
     range::RangePair,
     suffix_array::{
+        self,
+        Reversed,
         SparseSuffixArray,
-        SuffixArray
+        TextPosition
     }
 };
 
-/// Bidirectional FM index
+/// The forward BWT with its sentinel, the sparse suffix array and the forward occurence table
+type ForwardParts<A, T, O, B> = (PackedAlphabetString<A>, usize, SparseSuffixArray<T, B>, O);
+
+/// Bidirectional FM index, storing text positions as `T` and counting occurences with `O` and
+/// marking the sampled suffix array positions with `B`
 #[derive(Serialize, Deserialize, Debug)]
-pub struct BidirectionalFMIndex<A: Alphabet> {
-    /// The original text
-    text: AlphabetString<A>,
+pub struct BidirectionalFMIndex<
+    A: Alphabet,
+    T: TextPosition = u32,
+    O: Occurences = OccurenceTable,
+    B: RankSelect = Bitvec
+> {
+    /// The original text, left out of a self-index
+    text: Option<PackedAlphabetString<A>>,
+
+    /// The alphabet of the text
+    alphabet: A,
 
-    /// Burrows Wheeler Transform of the original text
-    bwt: AlphabetString<A>,
+    /// Length of the text
+    text_length: usize,
 
     /// Counts array
     counts: Vec<usize>,
 
     /// The sparse suffix array
-    sparse_sa: SparseSuffixArray,
+    sparse_sa: SparseSuffixArray<T, B>,
 
     /// Forward occurence table
-    normal_occurence_table: OccurenceTable,
+    normal_occurence_table: O,
 
     /// Backward occurence table
-    reversed_occurence_table: OccurenceTable
+    reversed_occurence_table: O
 }
 
 impl<A: Alphabet> BidirectionalFMIndex<A> {
+    /// Construct a new bidirectional FM index from a text, with the default position type and
+    /// backends
     pub fn new(text: AlphabetString<A>, sparseness_factor: u32) -> Self {
-        let text_length = text.len();
+        return Self::new_with_types(text, sparseness_factor);
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> BidirectionalFMIndex<A, T, O, B> {
+    /// Construct a new bidirectional FM index from a text, with the position type and backends of
+    /// the index
+    pub fn new_with_types(text: AlphabetString<A>, sparseness_factor: u32) -> Self {
+        return Self::with_options(text, sparseness_factor, &ConstructionOptions::default())
+            .expect("construction without a memory budget can not fail")
+            .0;
+    }
+
+    /// Construct a new bidirectional FM index from a text, reporting the peak memory usage. With a
+    /// single thread only one suffix array is alive at any time, with more threads the forward and
+    /// reversed halves are built concurrently.
+    pub fn with_options(
+        text: AlphabetString<A>,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(Self, ConstructionStats)> {
+        return parallel::install(options.threads, || {
+            Self::build(text, sparseness_factor, options)
+        });
+    }
+
+    fn build(
+        text: AlphabetString<A>,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(Self, ConstructionStats)> {
+        let memory = MemoryTracker::new(options.memory_budget);
+        memory.allocate(text.len())?;
+
+        let (forward, reversed) = parallel::join(
+            || Self::build_forward(&text, sparseness_factor, &memory),
+            || Self::build_reversed(&text, &memory)
+        );
+
+        let (bwt, sentinel, sparse_sa, normal_occurence_table) = forward?;
+        let reversed_occurence_table = reversed?;
+
+        // Initialize the counts table
+        let mut counts = vec![0; bwt.alphabet.len()];
+        Self::initialize_counts(&mut counts, &bwt, sentinel);
+
+        // The occurence tables give access to the BWT
+        memory.free(bwt.size_in_bytes());
+        drop(bwt);
+
+        let packed_text = if options.self_index {
+            None
+        } else {
+            let packed_text = PackedAlphabetString::from(&text);
+            memory.allocate(packed_text.size_in_bytes())?;
+            Some(packed_text)
+        };
+
+        let index = BidirectionalFMIndex {
+            text:                     packed_text,
+            alphabet:                 text.alphabet.clone(),
+            text_length:              text.len(),
+            counts:                   counts,
+            sparse_sa:                sparse_sa,
+            normal_occurence_table:   normal_occurence_table,
+            reversed_occurence_table: reversed_occurence_table
+        };
 
+        return Ok((index, memory.stats()));
+    }
+
+    /// Build the forward BWT, the sparse suffix array and the forward occurence table
+    fn build_forward(
+        text: &AlphabetString<A>,
+        sparseness_factor: u32,
+        memory: &MemoryTracker
+    ) -> Result<ForwardParts<A, T, O, B>> {
         // Create the suffix array for the forward text
-        let forward_sa = SuffixArray::new(&text).into_parts().1;
+        let forward_sa = suffix_array::construct::<T, _>(&text[..], text.alphabet.len(), memory)?;
 
         // Create the forward BWT from the forward suffix array
-        let mut forward_bwt = AlphabetString::<A>::new(text_length + 1);
-        let forward_sentinel = Self::forward_bwt_from_sa(&mut forward_bwt, &forward_sa, &text);
+        let (forward_bwt, forward_sentinel) = Self::forward_bwt_from_sa(&forward_sa, text);
+        memory.allocate(forward_bwt.size_in_bytes())?;
+
+        // Sample the forward suffix array before it is dropped
+        let sparse_sa = SparseSuffixArray::from_sa(&forward_sa, sparseness_factor);
+        memory.allocate(sparse_sa.size_in_bytes())?;
+        memory.free(forward_sa.len() * mem::size_of::<T>());
+        drop(forward_sa);
 
         // Create the forward occurence table
-        let normal_occurence_table = OccurenceTable::from_bwt(&forward_bwt, forward_sentinel);
+        let normal_occurence_table = O::from_bwt(&forward_bwt, forward_sentinel);
+        memory.allocate(normal_occurence_table.size_in_bytes())?;
+
+        return Ok((forward_bwt, forward_sentinel, sparse_sa, normal_occurence_table));
+    }
 
+    /// Build the backward occurence table
+    fn build_reversed(text: &AlphabetString<A>, memory: &MemoryTracker) -> Result<O> {
         // Create the suffix array for the reversed text
-        let backward_sa = SuffixArray::new(
-            &text
-                .clone()
-                .into_iter()
-                .rev()
-                .collect::<Vec<AlphabetIndex>>()
-        )
-        .into_parts()
-        .1;
+        let backward_sa =
+            suffix_array::construct::<T, _>(&Reversed(text), text.alphabet.len(), memory)?;
 
         // Create the reversed BWT from the backward suffix array
-        let mut backward_bwt = AlphabetString::<A>::new(text_length + 1);
-        let backward_sentinel = Self::backward_bwt_from_sa(&mut backward_bwt, &backward_sa, &text);
+        let (backward_bwt, backward_sentinel) = Self::backward_bwt_from_sa(&backward_sa, text);
+        memory.allocate(backward_bwt.size_in_bytes())?;
+
+        memory.free(backward_sa.len() * mem::size_of::<T>());
+        drop(backward_sa);
 
         // Create the backward occurence table
-        let reversed_occurence_table = OccurenceTable::from_bwt(&backward_bwt, backward_sentinel);
+        let reversed_occurence_table = O::from_bwt(&backward_bwt, backward_sentinel);
+        memory.allocate(reversed_occurence_table.size_in_bytes())?;
+        memory.free(backward_bwt.size_in_bytes());
 
-        // Initialize the counts table
-        let mut counts = vec![0; forward_bwt.alphabet.len()];
-        Self::initialize_counts(&mut counts, &forward_bwt, forward_sentinel);
-
-        BidirectionalFMIndex {
-            text: text,
-            bwt: forward_bwt,
-            counts: counts,
-            sparse_sa: SparseSuffixArray::from_sa(&forward_sa, sparseness_factor),
-            normal_occurence_table: normal_occurence_table,
-            reversed_occurence_table: reversed_occurence_table
-        }
+        return Ok(reversed_occurence_table);
     }
 
     /// Construct the Burrows Wheeler Transformation from the suffix array
     fn forward_bwt_from_sa(
-        bwt: &mut AlphabetString<A>,
-        sa: &Vec<u32>,
+        sa: &Vec<T>,
         text: &AlphabetString<A>
-    ) -> usize {
-        let mut sentinel = 0;
-
-        for i in 0 .. sa.len() {
-            if sa[i] == 0 {
-                bwt[i] = 0;
-                sentinel = i;
-            } else {
-                bwt[i] = text[sa[i] as usize - 1];
+    ) -> (PackedAlphabetString<A>, usize) {
+        let characters_per_word = 64 / text.alphabet.bits();
+
+        let parts = parallel::map_chunks(sa.len(), characters_per_word, |chunk| {
+            let mut bwt = PackedAlphabetString::with_alphabet(chunk.len(), text.alphabet.clone());
+            let mut sentinel = None;
+
+            for (i, value) in sa[chunk.clone()].iter().enumerate() {
+                if *value == T::zero() {
+                    bwt.set(i, 0);
+                    sentinel = Some(chunk.start + i);
+                } else {
+                    bwt.set(i, text[value.as_usize() - 1]);
+                }
             }
-        }
 
-        return sentinel;
+            return (bwt, sentinel);
+        });
+
+        let sentinel = parts
+            .iter()
+            .find_map(|(_, sentinel)| *sentinel)
+            .unwrap_or(0);
+        let parts = parts.into_iter().map(|(bwt, _)| bwt).collect();
+
+        return (PackedAlphabetString::concat(parts, text.alphabet.clone()), sentinel);
     }
 
     /// Construct the reversed Burrows Wheeler Transformation from the backward suffix array
     fn backward_bwt_from_sa(
-        bwt: &mut AlphabetString<A>,
-        sa: &Vec<u32>,
+        sa: &Vec<T>,
         text: &AlphabetString<A>
-    ) -> usize {
-        let mut sentinel = 0;
-
-        for i in 0 .. sa.len() {
-            if sa[i] == 0 {
-                bwt[i] = 0;
-                sentinel = i;
-            } else {
-                bwt[i] = text[text.len() - sa[i] as usize];
+    ) -> (PackedAlphabetString<A>, usize) {
+        let characters_per_word = 64 / text.alphabet.bits();
+
+        let parts = parallel::map_chunks(sa.len(), characters_per_word, |chunk| {
+            let mut bwt = PackedAlphabetString::with_alphabet(chunk.len(), text.alphabet.clone());
+            let mut sentinel = None;
+
+            for (i, value) in sa[chunk.clone()].iter().enumerate() {
+                if *value == T::zero() {
+                    bwt.set(i, 0);
+                    sentinel = Some(chunk.start + i);
+                } else {
+                    bwt.set(i, text[text.len() - value.as_usize()]);
+                }
             }
-        }
 
-        return sentinel;
+            return (bwt, sentinel);
+        });
+
+        let sentinel = parts
+            .iter()
+            .find_map(|(_, sentinel)| *sentinel)
+            .unwrap_or(0);
+        let parts = parts.into_iter().map(|(bwt, _)| bwt).collect();
+
+        return (PackedAlphabetString::concat(parts, text.alphabet.clone()), sentinel);
     }
 
     /// Construct the counts table
-    fn initialize_counts(counts: &mut Vec<usize>, bwt: &AlphabetString<A>, sentinel: usize) {
+    fn initialize_counts(counts: &mut Vec<usize>, bwt: &PackedAlphabetString<A>, sentinel: usize) {
         // Calculate counts
-        for (i, char_i) in bwt.iter().enumerate() {
-            if i == sentinel {
-                continue;
+        let chunk_counts = parallel::map_chunks(bwt.len(), 1, |chunk| {
+            let mut chunk_counts = vec![0; bwt.alphabet.len()];
+
+            for i in chunk {
+                if i != sentinel {
+                    chunk_counts[bwt.get(i) as usize] += 1;
+                }
             }
 
-            counts[(*char_i) as usize] += 1;
+            return chunk_counts;
+        });
+
+        for chunk_counts in chunk_counts {
+            for (char_i, count) in chunk_counts.into_iter().enumerate() {
+                counts[char_i] += count;
+            }
         }
 
         // Calculate the cumulative sum
@@ -147,6 +305,359 @@ impl<A: Alphabet> BidirectionalFMIndex<A> {
         }
     }
 
+    /// Get the alphabet of the indexed text
+    pub fn alphabet(&self) -> &A {
+        return &self.alphabet;
+    }
+
+    /// Get the length of the indexed text
+    pub fn text_length(&self) -> usize {
+        return self.text_length;
+    }
+
+    /// Check whether the original text is left out of the index
+    pub fn is_self_index(&self) -> bool {
+        return self.text.is_none();
+    }
+
+    /// Drop the original text, substrings are extracted from the forward BWT afterwards
+    pub fn drop_text(&mut self) {
+        self.text = None;
+    }
+
+    /// Get the ranges over both suffix arrays that match the empty string
+    pub fn full_range(&self) -> RangePair<usize> {
+        return RangePair::from((0, self.text_length + 1, 0, self.text_length + 1));
+    }
+
+    /// Find the previous character using the LF property over the forward BWT
+    fn find_lf(&self, k: usize) -> usize {
+        if k == self.normal_occurence_table.sentinel() {
+            return 0;
+        }
+
+        let char_i = self.normal_occurence_table.symbol(k) as usize;
+        return self.counts[char_i] + self.normal_occurence_table.occ(char_i, k);
+    }
+
+    /// Find the correct position in the original text
+    pub(crate) fn find_sa(&self, k: usize) -> T {
+        let mut i = k;
+        let mut j = 0;
+        while !self.sparse_sa.contains(i) {
+            i = self.find_lf(i);
+            j += 1;
+        }
+
+        return self.sparse_sa[i] + T::from_usize(j);
+    }
+
+    /// Locate the occurences of a match lazily, only the forward range is used
+    pub fn locate(&self, range_pair: &RangePair<usize>) -> Locate<'_, Self, T> {
+        return Locate::new(self, range_pair.normal_range);
+    }
+
+    /// Perform an approximate match for a given pattern in its own direction, allowing at most
+    /// `k` substitutions. Branches are pruned as soon as they exceed `k` mismatches.
+    pub fn hamming_match(&self, pattern: &AlphabetPattern<A>, k: usize) -> Vec<HammingMatch<T>> {
+        let mut occurences = vec![];
+
+        let m = pattern.len();
+
+        // Partial matches: the current ranges, the amount of characters added so far and the
+        // mismatched pattern positions
+        let mut stack = vec![(self.full_range(), 0, vec![])];
+
+        while let Some((range_pair, i, mut mismatches)) = stack.pop() {
+            if i == m {
+                mismatches.sort();
+
+                occurences.extend(self.locate(&range_pair).map(|position| HammingMatch {
+                    position:   position,
+                    mismatches: mismatches.clone()
+                }));
+
+                continue;
+            }
+
+            let mut range_pair_new = range_pair.clone();
+            for char_i in 0 .. self.alphabet.len() {
+                let mismatch = char_i != pattern[i] as usize;
+
+                if mismatch && mismatches.len() == k {
+                    continue;
+                }
+
+                let extended = match pattern.direction() {
+                    Direction::FORWARD => {
+                        self.add_char_right(char_i, &range_pair, &mut range_pair_new)
+                    }
+                    Direction::BACKWARD => {
+                        self.add_char_left(char_i, &range_pair, &mut range_pair_new)
+                    }
+                };
+
+                if extended {
+                    let mut new_mismatches = mismatches.clone();
+                    if mismatch {
+                        new_mismatches.push(match pattern.direction() {
+                            Direction::FORWARD => i,
+                            Direction::BACKWARD => m - i - 1
+                        });
+                    }
+
+                    stack.push((range_pair_new.clone(), i + 1, new_mismatches));
+                }
+            }
+        }
+
+        occurences.sort_by_key(|occurence| occurence.position);
+
+        return occurences;
+    }
+
+    /// Perform an approximate match for a given pattern with a search scheme, allowing at most
+    /// as many errors as the scheme does. The pattern is read in its original order, whatever its
+    /// direction. Every start position is reported once, with its fewest errors.
+    pub fn search_scheme_match(
+        &self,
+        pattern: &AlphabetPattern<A>,
+        scheme: &SearchScheme,
+        distance: Distance
+    ) -> Result<Vec<ApproximateMatch<T>>> {
+        let partition = scheme.partition(pattern.len())?;
+
+        let mut occurences = vec![];
+
+        for search in scheme.searches() {
+            // The parts in the order of the search, read in the direction they are added in
+            let parts: Vec<AlphabetPattern<A>> = (0 .. search.parts())
+                .map(|i| {
+                    let range = partition[search.order()[i]];
+                    let direction = match search.extends_right(i) {
+                        true => Direction::FORWARD,
+                        false => Direction::BACKWARD
+                    };
+
+                    pattern.slice(range.start, range.end, direction)
+                })
+                .collect();
+
+            match distance {
+                Distance::HAMMING => self.hamming_search(search, &parts, &mut occurences),
+                Distance::EDIT => {
+                    self.edit_search(search, &parts, 0, &self.full_range(), (0, 0), &mut occurences)
+                }
+            }
+        }
+
+        if distance == Distance::EDIT {
+            return Ok(index::filter_redundant(occurences, pattern.len() + scheme.max_errors()));
+        }
+
+        occurences.sort_by_key(|occurence| (occurence.position, occurence.distance));
+        occurences.dedup_by_key(|occurence| occurence.position);
+
+        return Ok(occurences);
+    }
+
+    /// Perform an approximate match for a given pattern allowing at most `k` errors, using the
+    /// built-in search scheme for `k`, which is only optimal up to 3 errors. Patterns shorter than
+    /// the amount of parts of that scheme are matched with a single search.
+    pub fn approximate_match(
+        &self,
+        pattern: &AlphabetPattern<A>,
+        k: usize,
+        distance: Distance
+    ) -> Result<Vec<ApproximateMatch<T>>> {
+        let scheme = match SearchScheme::builtin(k) {
+            Some(scheme) if pattern.len() >= scheme.parts() => scheme,
+            Some(_) => SearchScheme::single(k),
+            None => bail!(ErrorKind::InvalidSearchScheme(format!(
+                "there is no built-in scheme for {} errors, pass a search scheme instead",
+                k
+            )))
+        };
+
+        return self.search_scheme_match(pattern, &scheme, distance);
+    }
+
+    /// Follow a search allowing substitutions only
+    fn hamming_search(
+        &self,
+        search: &Search,
+        parts: &[AlphabetPattern<A>],
+        occurences: &mut Vec<ApproximateMatch<T>>
+    ) {
+        let length = parts.iter().map(|part| part.len()).sum();
+
+        // Partial matches: the current ranges, the current part, the amount of characters of that
+        // part added so far and the amount of mismatches
+        let mut stack = vec![(self.full_range(), 0, 0, 0)];
+
+        while let Some((range_pair, i, j, errors)) = stack.pop() {
+            if j == parts[i].len() {
+                if errors < search.lower()[i] {
+                    continue;
+                }
+
+                if i + 1 < parts.len() {
+                    stack.push((range_pair, i + 1, 0, errors));
+                    continue;
+                }
+
+                occurences.extend(self.locate(&range_pair).map(|position| ApproximateMatch {
+                    position: position,
+                    length:   length,
+                    distance: errors
+                }));
+
+                continue;
+            }
+
+            let mut range_pair_new = range_pair.clone();
+            for char_i in 0 .. self.alphabet.len() {
+                let mismatch = char_i != parts[i][j] as usize;
+
+                if mismatch && errors == search.upper()[i] {
+                    continue;
+                }
+
+                let extended = match search.extends_right(i) {
+                    true => self.add_char_right(char_i, &range_pair, &mut range_pair_new),
+                    false => self.add_char_left(char_i, &range_pair, &mut range_pair_new)
+                };
+
+                if extended {
+                    stack.push((range_pair_new.clone(), i, j + 1, errors + mismatch as usize));
+                }
+            }
+        }
+    }
+
+    /// Follow a search allowing edits from part `i` on, after matching `length` characters of the
+    /// text with `errors` edits
+    fn edit_search(
+        &self,
+        search: &Search,
+        parts: &[AlphabetPattern<A>],
+        i: usize,
+        range_pair: &RangePair<usize>,
+        (errors, length): (usize, usize),
+        occurences: &mut Vec<ApproximateMatch<T>>
+    ) {
+        if i == parts.len() {
+            occurences.extend(self.locate(range_pair).map(|position| ApproximateMatch {
+                position: position,
+                length:   length,
+                distance: errors
+            }));
+
+            return;
+        }
+
+        let part = &parts[i];
+
+        // Edits still allowed in this part
+        let b = search.upper()[i] - errors;
+
+        let mut matrix = BandedMatrix::new(part.len(), b);
+
+        // Without any text characters, the part is deleted entirely
+        if matrix.in_final_column(0) && errors + matrix.final_column(0) >= search.lower()[i] {
+            let distance = errors + matrix.final_column(0);
+            self.edit_search(search, parts, i + 1, range_pair, (distance, length), occurences);
+        }
+
+        let extend = |range_pair: &RangePair<usize>, row: usize, stack: &mut Vec<_>| {
+            let mut range_pair_new = range_pair.clone();
+            for char_i in 0 .. self.alphabet.len() {
+                let extended = match search.extends_right(i) {
+                    true => self.add_char_right(char_i, range_pair, &mut range_pair_new),
+                    false => self.add_char_left(char_i, range_pair, &mut range_pair_new)
+                };
+
+                if extended {
+                    stack.push((range_pair_new.clone(), row + 1, char_i as u8));
+                }
+            }
+        };
+
+        // Partial matches of this part: the current ranges, the matrix row and its character
+        let mut stack = vec![];
+        extend(range_pair, 0, &mut stack);
+
+        while let Some((range_pair, row, char_i)) = stack.pop() {
+            let min_edit_distance = matrix.update_row(part, row, char_i);
+
+            if min_edit_distance <= b && row < part.len() + b {
+                extend(&range_pair, row, &mut stack);
+            }
+
+            if matrix.in_final_column(row) {
+                let distance = matrix.final_column(row);
+
+                if distance <= b && errors + distance >= search.lower()[i] {
+                    self.edit_search(
+                        search,
+                        parts,
+                        i + 1,
+                        &range_pair,
+                        (errors + distance, length + row),
+                        occurences
+                    );
+                }
+            }
+        }
+    }
+
+    /// Align a pattern to the text span of one of its approximate occurences, producing a CIGAR
+    /// string under `distance`. The pattern is aligned in its original order, whatever its
+    /// direction.
+    pub fn align(
+        &self,
+        pattern: &AlphabetPattern<A>,
+        occurence: &ApproximateMatch<T>,
+        distance: Distance
+    ) -> Result<Alignment<T>> {
+        let text = self.extract(occurence.position.as_usize(), occurence.length)?;
+
+        return index::align(pattern, &text, occurence, distance);
+    }
+
+    /// Extract `length` characters of the original text starting at `start`. Without the text,
+    /// they are reconstructed by LF mapping over the forward BWT.
+    pub fn extract(&self, start: usize, length: usize) -> Result<AlphabetString<A>> {
+        if start > self.text_length || length > self.text_length - start {
+            bail!(ErrorKind::InvalidRange(start, length, self.text_length));
+        }
+
+        let mut result = AlphabetString::with_alphabet(length, self.alphabet.clone());
+
+        if let Some(text) = &self.text {
+            for i in 0 .. length {
+                result[i] = text.get(start + i);
+            }
+
+            return Ok(result);
+        }
+
+        // The row of position `p` holds the character at `p - 1` in the forward BWT
+        let (position, mut row) = self.sparse_sa.next_isa_sample(start + length);
+        for p in (start .. position).rev() {
+            let char_i = self.normal_occurence_table.symbol(row);
+
+            if p < start + length {
+                result[p - start] = char_i;
+            }
+
+            row = self.counts[char_i as usize]
+                + self.normal_occurence_table.occ(char_i as usize, row);
+        }
+
+        return Ok(result);
+    }
+
     pub fn add_char_left(
         &self,
         char_i: usize,
@@ -213,7 +724,7 @@ impl<A: Alphabet> BidirectionalFMIndex<A> {
 
     /// Perform an exact match for a given pattern
     pub fn exact_match(&self, pattern: &AlphabetPattern<A>) -> RangePair<usize> {
-        let mut range_pair = RangePair::from((0, self.text.len() + 1, 0, self.text.len() + 1));
+        let mut range_pair = self.full_range();
 
         match pattern.direction() {
             Direction::FORWARD => {
@@ -243,6 +754,94 @@ impl<A: Alphabet> BidirectionalFMIndex<A> {
 
         return range_pair;
     }
+
+    /// Perform an exact match for a pattern and its reverse complement, only strands on which
+    /// the pattern occurs are reported
+    pub fn exact_match_both_strands(
+        &self,
+        pattern: &AlphabetPattern<A>
+    ) -> Result<Vec<(Strand, RangePair<usize>)>> {
+        let reverse_complement = match pattern.reverse_complement() {
+            Some(reverse_complement) => reverse_complement,
+            None => bail!(ErrorKind::MissingComplement)
+        };
+
+        let result = vec![
+            (Strand::FORWARD, self.exact_match(pattern)),
+            (Strand::REVERSE, self.exact_match(&reverse_complement)),
+        ];
+
+        return Ok(result
+            .into_iter()
+            .filter(|(_, range_pair)| !range_pair.empty())
+            .collect());
+    }
+
+    /// Perform an exact match for a pattern with ambiguous characters, matching every concrete
+    /// sequence the pattern stands for. Unlike `exact_match`, which follows a single range, every
+    /// concrete sequence that occurs is reported with its own range.
+    pub fn ambiguous_exact_match(
+        &self,
+        pattern: &AmbiguousPattern<A>
+    ) -> Vec<AmbiguousMatch<A, RangePair<usize>>> {
+        let mut matches = vec![];
+
+        // Partial matches: the current ranges and the characters added so far
+        let mut stack = vec![(self.full_range(), vec![])];
+
+        while let Some((range_pair, characters)) = stack.pop() {
+            let i = characters.len();
+
+            if i == pattern.len() {
+                let mut sequence = AlphabetString::with_alphabet(i, self.alphabet().clone());
+                for (j, char_i) in characters.iter().enumerate() {
+                    match pattern.direction() {
+                        Direction::FORWARD => sequence[j] = *char_i,
+                        Direction::BACKWARD => sequence[i - j - 1] = *char_i
+                    }
+                }
+
+                matches.push(AmbiguousMatch {
+                    sequence,
+                    range: range_pair
+                });
+
+                continue;
+            }
+
+            let mut range_pair_new = range_pair.clone();
+            for char_i in pattern[i].iter() {
+                let extended = match pattern.direction() {
+                    Direction::FORWARD => {
+                        self.add_char_right(*char_i as usize, &range_pair, &mut range_pair_new)
+                    }
+                    Direction::BACKWARD => {
+                        self.add_char_left(*char_i as usize, &range_pair, &mut range_pair_new)
+                    }
+                };
+
+                if extended {
+                    let mut new_characters = characters.clone();
+                    new_characters.push(*char_i);
+
+                    stack.push((range_pair_new.clone(), new_characters));
+                }
+            }
+        }
+
+        // Different sequences have disjoint ranges, this sorts them lexicographically
+        matches.sort_by_key(|m| m.range.normal_range.start);
+
+        return matches;
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Locator<T>
+    for BidirectionalFMIndex<A, T, O, B>
+{
+    fn find_sa(&self, k: usize) -> T {
+        return BidirectionalFMIndex::find_sa(self, k);
+    }
 }
 
 // ======================================================================
@@ -251,17 +850,49 @@ impl<A: Alphabet> BidirectionalFMIndex<A> {
 
 #[cfg(test)]
 mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
     use crate::{
         alphabet::{
             Alphabet,
+            AlphabetIndex,
             AlphabetPattern,
             AlphabetString,
+            AmbiguousPattern,
             DNAAlphabet,
-            Direction
+            Direction,
+            PackedAlphabetString
+        },
+        bitvector::InterleavedOccurenceTable,
+        construction::ConstructionOptions,
+        errors::ErrorKind,
+        index::{
+            bidirectional_fm_index::BidirectionalFMIndex,
+            fm_index::FMIndex,
+            search_scheme::{
+                Distance,
+                Search,
+                SearchScheme
+            },
+            ApproximateMatch,
+            Strand
         },
-        index::bidirectional_fm_index::BidirectionalFMIndex,
         range::RangePair,
-        suffix_array::SuffixArray
+        suffix_array::SuffixArray,
+        testing::{
+            mutate,
+            naive_hamming,
+            random_text,
+            substitute
+        },
+        wavelet_tree::{
+            BalancedWaveletTree,
+            HuffmanWaveletTree
+        }
     };
 
     const INPUT: &str = "AACTAGGGCAATGTTCAACG";
@@ -282,8 +913,12 @@ mod tests {
 
         let bwt_result = AlphabetString::<DNAAlphabet>::from(BWT);
 
-        let mut bwt = AlphabetString::new(21);
-        BidirectionalFMIndex::<DNAAlphabet>::forward_bwt_from_sa(&mut bwt, &suffix_array, &input);
+        let (packed_bwt, sentinel) =
+            BidirectionalFMIndex::<DNAAlphabet>::forward_bwt_from_sa(&suffix_array, &input);
+
+        let bwt: Vec<AlphabetIndex> = packed_bwt.iter().collect();
+
+        assert_eq!(sentinel, BWT_SENTINEL);
 
         assert_eq!(bwt[0 .. BWT_SENTINEL], bwt_result[0 .. BWT_SENTINEL]);
         assert_eq!(bwt[BWT_SENTINEL + 1 .. 21], bwt_result[BWT_SENTINEL + 1 .. 21]);
@@ -298,8 +933,12 @@ mod tests {
 
         let bwt_result = AlphabetString::<DNAAlphabet>::from(BWT_REV);
 
-        let mut bwt = AlphabetString::new(21);
-        BidirectionalFMIndex::<DNAAlphabet>::backward_bwt_from_sa(&mut bwt, &suffix_array, &input);
+        let (packed_bwt, sentinel) =
+            BidirectionalFMIndex::<DNAAlphabet>::backward_bwt_from_sa(&suffix_array, &input);
+
+        let bwt: Vec<AlphabetIndex> = packed_bwt.iter().collect();
+
+        assert_eq!(sentinel, BWT_REV_SENTINEL);
 
         assert_eq!(bwt[0 .. BWT_REV_SENTINEL], bwt_result[0 .. BWT_REV_SENTINEL]);
         assert_eq!(bwt[BWT_REV_SENTINEL + 1 .. 21], bwt_result[BWT_REV_SENTINEL + 1 .. 21]);
@@ -307,7 +946,7 @@ mod tests {
 
     #[test]
     fn test_initialize_counts() {
-        let bwt = AlphabetString::<DNAAlphabet>::from(BWT);
+        let bwt = PackedAlphabetString::<DNAAlphabet>::from(BWT);
 
         let mut counts = vec![0; DNAAlphabet::default().len()];
         BidirectionalFMIndex::<DNAAlphabet>::initialize_counts(&mut counts, &bwt, BWT_SENTINEL);
@@ -467,4 +1106,484 @@ mod tests {
         assert_eq!(index.exact_match(&exact_match_end), exact_match_end_results);
         assert_eq!(index.exact_match(&exact_match_not), exact_match_not_results);
     }
+
+    #[test]
+    fn test_ambiguous_exact_match() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
+
+        let patterns = vec!["AAY", "RAC", "NCG", "AAC", "CCN", "GGK"];
+
+        let results: Vec<Vec<(&str, RangePair<usize>)>> = vec![
+            vec![("AAC", RangePair::from((1, 3, 8, 10))), ("AAT", RangePair::from((3, 4, 17, 18)))],
+            vec![("AAC", RangePair::from((1, 3, 8, 10)))],
+            vec![("ACG", RangePair::from((4, 5, 13, 14)))],
+            vec![("AAC", RangePair::from((1, 3, 8, 10)))],
+            vec![],
+            vec![("GGG", RangePair::from((15, 16, 15, 16)))],
+        ];
+
+        for direction in [Direction::FORWARD, Direction::BACKWARD] {
+            for i in 0 .. patterns.len() {
+                let mut pattern = AmbiguousPattern::<DNAAlphabet>::from(patterns[i]);
+                pattern.set_direction(direction);
+
+                let matches = index.ambiguous_exact_match(&pattern);
+
+                assert_eq!(matches.len(), results[i].len());
+
+                for (m, (sequence, range_pair)) in matches.iter().zip(results[i].iter()) {
+                    assert_eq!(*m.sequence, *AlphabetString::<DNAAlphabet>::from(*sequence));
+                    assert_eq!(m.range, *range_pair);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_match_both_strands() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
+
+        let patterns = vec!["GT", "CA", "TTG", "ACG"];
+
+        let results = vec![
+            vec![
+                (Strand::FORWARD, RangePair::from((16, 17, 19, 20))),
+                (Strand::REVERSE, RangePair::from((4, 6, 8, 10))),
+            ],
+            vec![
+                (Strand::FORWARD, RangePair::from((8, 10, 5, 7))),
+                (Strand::REVERSE, RangePair::from((19, 20, 16, 17))),
+            ],
+            vec![(Strand::REVERSE, RangePair::from((8, 10, 3, 5)))],
+            vec![(Strand::FORWARD, RangePair::from((4, 5, 13, 14)))],
+        ];
+
+        for direction in [Direction::FORWARD, Direction::BACKWARD] {
+            for i in 0 .. patterns.len() {
+                let pattern = AlphabetPattern::<DNAAlphabet>::new(patterns[i], direction);
+
+                assert_eq!(index.exact_match_both_strands(&pattern).unwrap(), results[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_match_u64() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
+        let index_u64 = BidirectionalFMIndex::<DNAAlphabet, u64>::new_with_types(
+            AlphabetString::from(INPUT),
+            1
+        );
+
+        for pattern in ["A", "CA", "GTT", "AACG", "CCC"] {
+            for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                let pattern = AlphabetPattern::<DNAAlphabet>::new(pattern, direction);
+
+                assert_eq!(index_u64.exact_match(&pattern), index.exact_match(&pattern));
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_match_occurence_backends() {
+        let mut rng = StdRng::seed_from_u64(14);
+
+        let text = random_text(&mut rng, 2_000);
+
+        let index =
+            BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 3);
+        let interleaved =
+            BidirectionalFMIndex::<DNAAlphabet, u32, InterleavedOccurenceTable>::new_with_types(
+                AlphabetString::from(text.as_str()),
+                3
+            );
+        let balanced =
+            BidirectionalFMIndex::<DNAAlphabet, u32, BalancedWaveletTree>::new_with_types(
+                AlphabetString::from(text.as_str()),
+                3
+            );
+        let huffman = BidirectionalFMIndex::<DNAAlphabet, u32, HuffmanWaveletTree>::new_with_types(
+            AlphabetString::from(text.as_str()),
+            3
+        );
+
+        for _ in 0 .. 50 {
+            let start = rng.gen_range(0 .. text.len() - 8);
+            let pattern = &text[start .. start + rng.gen_range(1 ..= 8)];
+
+            for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                let pattern = AlphabetPattern::<DNAAlphabet>::new(pattern, direction);
+
+                let expected = index.exact_match(&pattern);
+                assert_eq!(interleaved.exact_match(&pattern), expected);
+                assert_eq!(balanced.exact_match(&pattern), expected);
+                assert_eq!(huffman.exact_match(&pattern), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_options() {
+        let (index, stats) = BidirectionalFMIndex::<DNAAlphabet, u64>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &ConstructionOptions::default()
+        )
+        .unwrap();
+
+        // At least the text and the suffix array were alive at the same time
+        assert!(stats.peak_memory >= INPUT.len() + (INPUT.len() + 1) * 8);
+
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("AAC");
+        assert_eq!(index.exact_match(&pattern), RangePair::from((1, 3, 8, 10)));
+
+        // The peak memory is exactly the smallest budget that suffices
+        let options = ConstructionOptions {
+            memory_budget: Some(stats.peak_memory),
+            ..Default::default()
+        };
+        assert!(BidirectionalFMIndex::<DNAAlphabet, u64>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &options
+        )
+        .is_ok());
+
+        let options = ConstructionOptions {
+            memory_budget: Some(stats.peak_memory - 1),
+            ..Default::default()
+        };
+        match BidirectionalFMIndex::<DNAAlphabet, u64>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &options
+        ) {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(..) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+    }
+
+    #[test]
+    fn test_parallel() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let text = random_text(&mut rng, 10_000);
+
+        let (serial, _) = BidirectionalFMIndex::<DNAAlphabet>::with_options(
+            AlphabetString::from(text.as_str()),
+            4,
+            &ConstructionOptions::default()
+        )
+        .unwrap();
+
+        let options = ConstructionOptions {
+            threads: 4,
+            ..Default::default()
+        };
+        let (parallel, _) = BidirectionalFMIndex::<DNAAlphabet>::with_options(
+            AlphabetString::from(text.as_str()),
+            4,
+            &options
+        )
+        .unwrap();
+
+        assert_eq!(bincode::serialize(&serial).unwrap(), bincode::serialize(&parallel).unwrap());
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut rng = StdRng::seed_from_u64(18);
+
+        let text = random_text(&mut rng, 2_000);
+        let expected = AlphabetString::<DNAAlphabet>::from(text.as_str());
+
+        let index =
+            BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 16);
+
+        let options = ConstructionOptions {
+            self_index: true,
+            ..Default::default()
+        };
+        let (self_index, _) = BidirectionalFMIndex::<DNAAlphabet>::with_options(
+            AlphabetString::from(text.as_str()),
+            16,
+            &options
+        )
+        .unwrap();
+
+        assert!(self_index.is_self_index());
+
+        for _ in 0 .. 100 {
+            let start = rng.gen_range(0 .. text.len());
+            let length = rng.gen_range(0 ..= (text.len() - start).min(50));
+
+            assert_eq!(*index.extract(start, length).unwrap(), expected[start .. start + length]);
+            assert_eq!(
+                *self_index.extract(start, length).unwrap(),
+                expected[start .. start + length]
+            );
+        }
+
+        assert_eq!(*self_index.extract(1_990, 10).unwrap(), expected[1_990 ..]);
+        assert!(self_index.extract(1_990, 11).is_err());
+    }
+
+    #[test]
+    fn test_find_sa() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let sa_results: Vec<u32> =
+            vec![20, 16, 0, 9, 17, 1, 4, 10, 15, 8, 18, 2, 19, 7, 6, 5, 12, 3, 14, 11, 13];
+
+        for i in 0 .. sa_results.len() {
+            assert_eq!(index.find_sa(i), sa_results[i]);
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let mut rng = StdRng::seed_from_u64(20);
+
+        let text = random_text(&mut rng, 2_000);
+
+        let index = BidirectionalFMIndex::<DNAAlphabet, u64, HuffmanWaveletTree>::new_with_types(
+            AlphabetString::from(text.as_str()),
+            8
+        );
+
+        for _ in 0 .. 50 {
+            let start = rng.gen_range(0 .. text.len() - 8);
+            let pattern = &text[start .. start + rng.gen_range(1 ..= 8)];
+
+            let expected: Vec<u64> = (0 ..= text.len() - pattern.len())
+                .filter(|i| text[*i ..].starts_with(pattern))
+                .map(|i| i as u64)
+                .collect();
+
+            for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                let pattern = AlphabetPattern::<DNAAlphabet>::new(pattern, direction);
+                let range_pair = index.exact_match(&pattern);
+
+                let locate = index.locate(&range_pair);
+                assert_eq!(locate.len(), expected.len());
+
+                let mut result: Vec<u64> = locate.collect();
+                result.sort();
+                assert_eq!(result, expected);
+
+                assert_eq!(index.locate(&range_pair).max_hits(1).count(), 1);
+            }
+        }
+
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("CCCCCCCCCCCCCCCCCCCC");
+        assert_eq!(index.locate(&index.exact_match(&pattern)).next(), None);
+    }
+
+    #[test]
+    fn test_hamming_match() {
+        let mut rng = StdRng::seed_from_u64(22);
+
+        let text = random_text(&mut rng, 2_000);
+
+        let index =
+            BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 8);
+
+        for _ in 0 .. 20 {
+            let length = rng.gen_range(6 ..= 12);
+            let start = rng.gen_range(0 .. text.len() - length);
+
+            let pattern = substitute(&mut rng, &text[start .. start + length], 1);
+
+            for k in 0 ..= 2 {
+                let expected = naive_hamming(&text, &pattern, k);
+
+                for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                    let pattern = AlphabetPattern::<DNAAlphabet>::new(&pattern, direction);
+
+                    let result: Vec<(usize, Vec<usize>)> = index
+                        .hamming_match(&pattern, k)
+                        .into_iter()
+                        .map(|m| (m.position as usize, m.mismatches))
+                        .collect();
+
+                    assert_eq!(result, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_approximate_match() {
+        let mut rng = StdRng::seed_from_u64(23);
+
+        let text = random_text(&mut rng, 1_000);
+
+        let index =
+            BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 8);
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 8);
+
+        for _ in 0 .. 30 {
+            let length = rng.gen_range(8 ..= 16);
+            let start = rng.gen_range(0 .. text.len() - length);
+
+            let pattern = mutate(&mut rng, &text[start .. start + length], 2);
+
+            for k in 0 ..= 3 {
+                let mut fm_pattern = AlphabetPattern::<DNAAlphabet>::from(pattern.as_str());
+                let expected_edit = fm_index.approximate_match(&mut fm_pattern, k);
+
+                let expected_hamming: Vec<(usize, usize, usize)> =
+                    naive_hamming(&text, &pattern, k)
+                        .into_iter()
+                        .map(|(start, mismatches)| (start, pattern.len(), mismatches.len()))
+                        .collect();
+
+                for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                    let pattern = AlphabetPattern::<DNAAlphabet>::new(&pattern, direction);
+
+                    let result = index
+                        .approximate_match(&pattern, k, Distance::EDIT)
+                        .unwrap();
+                    assert_eq!(result, expected_edit);
+
+                    let result: Vec<(usize, usize, usize)> = index
+                        .approximate_match(&pattern, k, Distance::HAMMING)
+                        .unwrap()
+                        .into_iter()
+                        .map(|m| (m.position as usize, m.length, m.distance))
+                        .collect();
+                    assert_eq!(result, expected_hamming);
+                }
+            }
+        }
+
+        let pattern = AlphabetPattern::<DNAAlphabet>::from(&text[100 .. 120]);
+        let mut fm_pattern = AlphabetPattern::<DNAAlphabet>::from(&text[100 .. 120]);
+        assert_eq!(
+            index
+                .approximate_match(&pattern, 4, Distance::EDIT)
+                .unwrap(),
+            fm_index.approximate_match(&mut fm_pattern, 4)
+        );
+        assert!(index
+            .approximate_match(&pattern, 5, Distance::EDIT)
+            .is_err());
+
+        // Patterns shorter than the amount of parts are matched with a single search
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("ACG");
+        let mut fm_pattern = AlphabetPattern::<DNAAlphabet>::from("ACG");
+        assert_eq!(
+            index
+                .approximate_match(&pattern, 2, Distance::EDIT)
+                .unwrap(),
+            fm_index.approximate_match(&mut fm_pattern, 2)
+        );
+
+        let result: Vec<(usize, usize, usize)> = index
+            .approximate_match(&pattern, 2, Distance::HAMMING)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.position as usize, m.length, m.distance))
+            .collect();
+        let expected: Vec<(usize, usize, usize)> = naive_hamming(&text, "ACG", 2)
+            .into_iter()
+            .map(|(start, mismatches)| (start, 3, mismatches.len()))
+            .collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_search_scheme_match() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
+
+        // Pigeonhole scheme: one of both parts has no errors
+        let scheme = SearchScheme::new(vec![
+            Search::new(vec![0, 1], vec![0, 0], vec![0, 1]).unwrap(),
+            Search::new(vec![1, 0], vec![0, 0], vec![0, 1]).unwrap(),
+        ])
+        .unwrap();
+
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("AACG");
+        let result: Vec<(usize, usize, usize)> = index
+            .search_scheme_match(&pattern, &scheme, Distance::EDIT)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.position as usize, m.length, m.distance))
+            .collect();
+        assert_eq!(result, [(0, 3, 1), (9, 4, 1), (16, 4, 0)]);
+
+        let mut fm_pattern = AlphabetPattern::<DNAAlphabet>::from("AACG");
+        assert_eq!(
+            index
+                .search_scheme_match(&pattern, &scheme, Distance::EDIT)
+                .unwrap(),
+            fm_index.approximate_match(&mut fm_pattern, 1)
+        );
+
+        let result: Vec<(usize, usize)> = index
+            .search_scheme_match(&pattern, &scheme, Distance::HAMMING)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.position as usize, m.distance))
+            .collect();
+        assert_eq!(result, [(0, 1), (9, 1), (16, 0)]);
+    }
+
+    #[test]
+    fn test_align() {
+        let index = BidirectionalFMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let pattern = AlphabetPattern::<DNAAlphabet>::new("AACG", Direction::BACKWARD);
+
+        let cigars = |distance| -> Vec<(usize, String)> {
+            index
+                .approximate_match(&pattern, 1, distance)
+                .unwrap()
+                .iter()
+                .map(|m| {
+                    let alignment = index.align(&pattern, m, distance).unwrap();
+                    (alignment.position as usize, alignment.cigar.to_string())
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            cigars(Distance::EDIT),
+            [(0, "3=1I".to_string()), (9, "2=1X1=".to_string()), (16, "4=".to_string())]
+        );
+        assert_eq!(
+            cigars(Distance::HAMMING),
+            [(0, "3=1X".to_string()), (9, "2=1X1=".to_string()), (16, "4=".to_string())]
+        );
+
+        // CTAG is four substitutions but only two edits away from ACTA
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("ACTA");
+        let occurence = |distance| ApproximateMatch {
+            position: 2,
+            length:   4,
+            distance: distance
+        };
+
+        let alignment = index
+            .align(&pattern, &occurence(4), Distance::HAMMING)
+            .unwrap();
+        assert_eq!(alignment.cigar.to_string(), "4X");
+
+        let alignment = index
+            .align(&pattern, &occurence(2), Distance::EDIT)
+            .unwrap();
+        assert_eq!(alignment.cigar.to_string(), "1I3=1D");
+
+        match index.align(&pattern, &occurence(2), Distance::HAMMING) {
+            Err(e) => match e.kind() {
+                ErrorKind::AlignmentFailed(2, 2) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the alignment did not fail")
+        }
+    }
 }
@@ -0,0 +1,306 @@
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetPattern,
+        AlphabetString,
+        Direction,
+        SeparatedAlphabet
+    },
+    bitvector::{
+        Bitvec,
+        OccurenceTable,
+        Occurences,
+        RankSelect
+    },
+    construction::{
+        ConstructionOptions,
+        ConstructionStats
+    },
+    errors::Result,
+    index::fm_index::FMIndex,
+    suffix_array::TextPosition
+};
+
+// ======================================================================
+// == CollectionBuilder
+// ======================================================================
+
+/// Builder for an FM index over a collection of named sequences
+pub struct CollectionBuilder<A: Alphabet> {
+    /// The alphabet of all sequences
+    alphabet: A,
+
+    /// Names of the sequences
+    names: Vec<String>,
+
+    /// The sequences
+    sequences: Vec<AlphabetString<A>>
+}
+
+impl<A: Alphabet> CollectionBuilder<A> {
+    pub fn new(alphabet: A) -> Self {
+        Self {
+            alphabet:  alphabet,
+            names:     vec![],
+            sequences: vec![]
+        }
+    }
+
+    /// Add a named sequence to the collection
+    pub fn add_sequence(&mut self, name: &str, sequence: AlphabetString<A>) -> &mut Self {
+        self.names.push(name.to_string());
+        self.sequences.push(sequence);
+
+        return self;
+    }
+
+    /// Concatenate the sequences with separators in between and index the result
+    pub fn build<T: TextPosition>(self, sparseness_factor: u32) -> FMIndexCollection<A, T> {
+        return self
+            .build_with_options(sparseness_factor, &ConstructionOptions::default())
+            .expect("construction without a memory budget can not fail")
+            .0;
+    }
+
+    /// Index the collection, reporting the peak memory usage of the index construction
+    pub fn build_with_options<T: TextPosition>(
+        self,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(FMIndexCollection<A, T>, ConstructionStats)> {
+        return self.build_with_backend(sparseness_factor, options);
+    }
+
+    /// Index the collection with another occurence table or rank/select bitvector for the
+    /// suffix array samples
+    pub fn build_with_backend<T: TextPosition, O: Occurences, B: RankSelect>(
+        self,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(FMIndexCollection<A, T, O, B>, ConstructionStats)> {
+        let alphabet = SeparatedAlphabet::new(self.alphabet);
+        let separator = alphabet.separator();
+
+        let text_length = self.sequences.iter().map(|s| s.len() + 1).sum::<usize>();
+        let mut text = AlphabetString::with_alphabet(0, alphabet);
+        text.reserve(text_length);
+
+        let mut starts = Vec::with_capacity(self.sequences.len());
+        for (i, sequence) in self.sequences.iter().enumerate() {
+            if i > 0 {
+                text.push(separator);
+            }
+
+            starts.push(T::from_usize(text.len()));
+            text.extend_from_slice(sequence);
+        }
+
+        let (index, stats) = FMIndex::with_options(text, sparseness_factor, options)?;
+
+        let collection = FMIndexCollection {
+            index:  index,
+            names:  self.names,
+            starts: starts
+        };
+
+        return Ok((collection, stats));
+    }
+}
+
+// ======================================================================
+// == FMIndexCollection
+// ======================================================================
+
+/// FM index over a collection of named sequences
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FMIndexCollection<
+    A: Alphabet,
+    T: TextPosition = u32,
+    O: Occurences = OccurenceTable,
+    B: RankSelect = Bitvec
+> {
+    /// Index over the concatenation of all sequences
+    index: FMIndex<SeparatedAlphabet<A>, T, O, B>,
+
+    /// Names of the sequences
+    names: Vec<String>,
+
+    /// Start position of every sequence in the concatenated text
+    starts: Vec<T>
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> FMIndexCollection<A, T, O, B> {
+    /// Get the name of a sequence
+    pub fn name(&self, sequence_id: usize) -> &str {
+        return &self.names[sequence_id];
+    }
+
+    /// Get the amount of sequences in the collection
+    pub fn sequences(&self) -> usize {
+        return self.names.len();
+    }
+
+    /// Translate a position in the concatenated text to a sequence and an offset in it
+    pub fn translate(&self, position: T) -> (usize, T) {
+        let sequence_id = self.starts.partition_point(|start| *start <= position) - 1;
+        return (sequence_id, position - self.starts[sequence_id]);
+    }
+
+    /// Find the sequence and offset of a suffix array position
+    pub fn locate(&self, k: usize) -> (usize, T) {
+        return self.translate(self.index.find_sa(k));
+    }
+
+    /// Perform an exact match for a given pattern
+    pub fn exact_match(&self, pattern: &mut AlphabetPattern<A>) -> Vec<(usize, T)> {
+        let range = self.index.backward_search(&mut self.separated(pattern));
+
+        return (range.start .. range.end).map(|k| self.locate(k)).collect();
+    }
+
+    /// Translate a pattern to the alphabet of the index, its characters keep their indices
+    fn separated(&self, pattern: &AlphabetPattern<A>) -> AlphabetPattern<SeparatedAlphabet<A>> {
+        let mut string =
+            AlphabetString::with_alphabet(pattern.len(), self.index.alphabet().clone());
+
+        for i in 0 .. pattern.len() {
+            string[i] = match pattern.direction() {
+                Direction::FORWARD => pattern[i],
+                Direction::BACKWARD => pattern[pattern.len() - i - 1]
+            };
+        }
+
+        return AlphabetPattern::from(string);
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        alphabet::{
+            AlphabetPattern,
+            AlphabetString,
+            DNAAlphabet,
+            Direction
+        },
+        bitvector::Bitvec,
+        construction::ConstructionOptions,
+        index::collection::{
+            CollectionBuilder,
+            FMIndexCollection
+        },
+        wavelet_tree::HuffmanWaveletTree
+    };
+
+    const SEQUENCES: [(&str, &str); 3] =
+        [("chr1", "AACTAGGGCA"), ("chr2", "ATGTTC"), ("chr3", "AACG")];
+
+    fn build(sparseness_factor: u32) -> FMIndexCollection<DNAAlphabet> {
+        let mut builder = CollectionBuilder::new(DNAAlphabet::default());
+
+        for (name, sequence) in SEQUENCES {
+            builder.add_sequence(name, AlphabetString::from(sequence));
+        }
+
+        return builder.build(sparseness_factor);
+    }
+
+    #[test]
+    fn test_names() {
+        let collection = build(1);
+
+        assert_eq!(collection.sequences(), 3);
+        assert_eq!(collection.name(0), "chr1");
+        assert_eq!(collection.name(1), "chr2");
+        assert_eq!(collection.name(2), "chr3");
+    }
+
+    #[test]
+    fn test_translate() {
+        let collection = build(1);
+
+        // AACTAGGGCA$ATGTTC$AACG
+        assert_eq!(collection.translate(0), (0, 0));
+        assert_eq!(collection.translate(9), (0, 9));
+        assert_eq!(collection.translate(11), (1, 0));
+        assert_eq!(collection.translate(16), (1, 5));
+        assert_eq!(collection.translate(18), (2, 0));
+        assert_eq!(collection.translate(21), (2, 3));
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let collection = build(3);
+
+        let patterns = vec!["A", "AAC", "GT", "CAA", "CAAT", "TCA", "AACTAGGGCA"];
+
+        let results: Vec<Vec<(usize, u32)>> = vec![
+            vec![(0, 0), (0, 1), (0, 4), (0, 9), (1, 0), (2, 0), (2, 1)],
+            vec![(0, 0), (2, 0)],
+            vec![(1, 2)],
+            // Would match over the boundaries between the sequences without separators
+            vec![],
+            vec![],
+            vec![],
+            vec![(0, 0)],
+        ];
+
+        for i in 0 .. patterns.len() {
+            for direction in [Direction::FORWARD, Direction::BACKWARD] {
+                let mut pattern = AlphabetPattern::<DNAAlphabet>::new(patterns[i], direction);
+
+                let mut result = collection.exact_match(&mut pattern);
+                result.sort();
+
+                assert_eq!(result, results[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_match_u64() {
+        let mut builder = CollectionBuilder::new(DNAAlphabet::default());
+
+        for (name, sequence) in SEQUENCES {
+            builder.add_sequence(name, AlphabetString::from(sequence));
+        }
+
+        let collection = builder.build::<u64>(3);
+
+        let mut result = collection.exact_match(&mut AlphabetPattern::from("AAC"));
+        result.sort();
+
+        assert_eq!(result, vec![(0, 0), (2, 0)]);
+        assert_eq!(collection.translate(16), (1, 5));
+    }
+
+    #[test]
+    fn test_exact_match_wavelet_tree() {
+        let mut builder = CollectionBuilder::new(DNAAlphabet::default());
+
+        for (name, sequence) in SEQUENCES {
+            builder.add_sequence(name, AlphabetString::from(sequence));
+        }
+
+        let (collection, _) = builder
+            .build_with_backend::<u32, HuffmanWaveletTree, Bitvec>(
+                3,
+                &ConstructionOptions::default()
+            )
+            .unwrap();
+
+        let mut result = collection.exact_match(&mut AlphabetPattern::from("A"));
+        result.sort();
+
+        assert_eq!(result, vec![(0, 0), (0, 1), (0, 4), (0, 9), (1, 0), (2, 0), (2, 1)]);
+    }
+}
@@ -0,0 +1,1044 @@
+use std::{
+    cell::RefCell,
+    cmp::{
+        min,
+        Ordering
+    },
+    env,
+    fs::{
+        self,
+        File
+    },
+    io::{
+        BufRead,
+        BufReader,
+        BufWriter,
+        Read,
+        Seek,
+        SeekFrom,
+        Write
+    },
+    mem,
+    path::{
+        Path,
+        PathBuf
+    },
+    process,
+    sync::atomic::{
+        self,
+        AtomicUsize
+    }
+};
+
+use serde::Serialize;
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetIndex,
+        PackedAlphabetString
+    },
+    bitvector::{
+        Bitvec,
+        Occurences,
+        RankSelect
+    },
+    construction::{
+        ConstructionOptions,
+        ConstructionStats,
+        MemoryTracker
+    },
+    errors::{
+        ErrorKind,
+        Result
+    },
+    index::fm_index::FMIndex,
+    io::Binary,
+    suffix_array::{
+        self,
+        Names,
+        SparseSuffixArray,
+        TextPosition
+    }
+};
+
+/// Amount of suffixes sorted at once without a memory budget or block size
+const DEFAULT_BLOCK_SIZE: usize = 1 << 24;
+
+/// Maximum amount of prefix buckets the suffixes are distributed over
+const MAX_BUCKETS: usize = 1 << 20;
+
+/// Marks the sentinel in the temporary BWT files
+const SENTINEL_BYTE: u8 = u8::MAX;
+
+/// Amount of words of the packed text read from its temporary file at once
+const PAGE_WORDS: usize = 1 << 10;
+
+/// Amount of pages of the packed text kept in memory
+const CACHED_PAGES: usize = 16;
+
+/// Square root of the period of the difference cover used to compare suffixes
+const COVER_ROOT: usize = 16;
+
+/// Period of the difference cover used to compare suffixes
+const COVER_PERIOD: usize = COVER_ROOT * COVER_ROOT;
+
+/// Maximum amount of temporary position files written at once
+const MAX_OPEN_BLOCKS: usize = 256;
+
+/// Size of the write buffer of every temporary position file
+const POSITIONS_BUFFER: usize = 1 << 13;
+
+// ======================================================================
+// == External construction
+// ======================================================================
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> FMIndex<A, T, O, B> {
+    /// Construct an FM index for a text streamed from `input` and write it to `output`, without
+    /// ever holding the complete text or suffix array in memory. Line breaks in the input are
+    /// ignored.
+    ///
+    /// The text is packed in `alphabet.bits()` bits per character into a temporary file, which is
+    /// read a few pages at a time. Only the finished index (the BWT, the occurence table and the
+    /// suffix array samples) must fit in memory, along with the text itself unless
+    /// `options.self_index` is set.
+    ///
+    /// The suffixes are distributed over blocks by their first characters, writing the positions
+    /// of every block to a temporary file in a single pass over the text. At most
+    /// `MAX_OPEN_BLOCKS` files are written at once, more blocks take more passes. Every block is
+    /// sorted on its own and its part of the BWT and the suffix array samples are written to
+    /// temporary files, which are assembled into the index afterwards.
+    ///
+    /// Suffixes are only compared on their first characters up to the next position of a
+    /// difference cover sample, whose suffixes are ranked beforehand. Long repeats thus cost no
+    /// more than `COVER_PERIOD` characters per comparison. The ranks of the difference cover
+    /// sample count towards the memory budget, as do the parts of the index assembled at the end.
+    pub fn build_external<R: Read, W: Write>(
+        input: R,
+        alphabet: A,
+        sparseness_factor: u32,
+        options: &ConstructionOptions,
+        output: BufWriter<W>
+    ) -> Result<ConstructionStats>
+    where
+        Self: Serialize
+    {
+        let memory = MemoryTracker::new(options.memory_budget);
+
+        let temp_dir = TempDir::new(options.temp_dir.clone())?;
+
+        let text = TextFile::write(input, alphabet, &temp_dir.file("text"), &memory)?;
+        let n = text.len();
+
+        let cover = DifferenceCover::<T>::new(&text, &memory)?;
+
+        let (prefix_length, blocks) = Self::plan_blocks(&text, options, &memory)?;
+
+        // The sampled positions are marked while the blocks are sorted
+        let mut bitvector = Bitvec::new(n + 1);
+        memory.allocate(bitvector.size_in_bytes())?;
+
+        // The rows of the sampled positions are only known once the blocks are sorted
+        let mut sparse_isa = vec![T::zero(); n / sparseness_factor as usize + 1];
+        memory.allocate(sparse_isa.len() * mem::size_of::<T>())?;
+
+        // Distribute the positions over the blocks by their prefixes, writing them to disk
+        for first in (0 .. blocks.len()).step_by(MAX_OPEN_BLOCKS) {
+            let last = min(first + MAX_OPEN_BLOCKS, blocks.len());
+            let keys = blocks[first].keys.start .. blocks[last - 1].keys.end;
+
+            memory.allocate((last - first) * POSITIONS_BUFFER)?;
+
+            let mut files = vec![];
+            for b in first .. last {
+                let file = File::create(temp_dir.path(b, "positions"))?;
+                files.push(BufWriter::with_capacity(POSITIONS_BUFFER, file));
+            }
+
+            let mut prefix_keys = PrefixKeys::new(&text, prefix_length);
+            for p in 0 ..= n {
+                let key = prefix_keys.next_key();
+
+                if keys.contains(&key) {
+                    let b = blocks.partition_point(|block| block.keys.end <= key);
+                    files[b - first].write_all(&(p as u64).to_le_bytes())?;
+                }
+            }
+
+            for file in files.iter_mut() {
+                file.flush()?;
+            }
+
+            memory.free((last - first) * POSITIONS_BUFFER);
+        }
+
+        // Sort every block and write its part of the BWT and the samples to disk
+        let mut i = 0;
+        for (b, block) in blocks.iter().enumerate() {
+            memory.allocate(block.size * mem::size_of::<T>())?;
+
+            let mut positions: Vec<T> = Vec::with_capacity(block.size);
+            read_positions(&temp_dir.path(b, "positions"), &mut positions)?;
+            fs::remove_file(temp_dir.path(b, "positions"))?;
+
+            positions.sort_unstable_by(|a, b| cover.compare(&text, a.as_usize(), b.as_usize()));
+
+            let mut bwt = BufWriter::new(File::create(temp_dir.path(b, "bwt"))?);
+            let mut samples = BufWriter::new(File::create(temp_dir.path(b, "samples"))?);
+
+            for position in positions.iter() {
+                let p = position.as_usize();
+
+                bwt.write_all(&[if p == 0 {
+                    SENTINEL_BYTE
+                } else {
+                    text.get(p - 1)
+                }])?;
+
+                if p % sparseness_factor as usize == 0 {
+                    bitvector.set(i, true);
+                    sparse_isa[p / sparseness_factor as usize] = T::from_usize(i);
+                    samples.write_all(&(p as u64).to_le_bytes())?;
+                }
+
+                i += 1;
+            }
+
+            bwt.flush()?;
+            samples.flush()?;
+
+            memory.free(block.size * mem::size_of::<T>());
+        }
+
+        memory.free(cover.size_in_bytes());
+        drop(cover);
+
+        bitvector.calculate_counts();
+
+        // Assemble the BWT and the sparse suffix array from the temporary files
+        let mut bwt = PackedAlphabetString::with_alphabet(n + 1, text.alphabet.clone());
+        memory.allocate(bwt.size_in_bytes())?;
+
+        let samples = bitvector.rank(n) + bitvector.get(n) as usize;
+        memory.allocate(samples * mem::size_of::<T>())?;
+
+        let mut sparse_sa = Vec::with_capacity(samples);
+
+        let mut i = 0;
+        let mut sentinel = 0;
+        for b in 0 .. blocks.len() {
+            let reader = BufReader::new(File::open(temp_dir.path(b, "bwt"))?);
+
+            for byte in reader.bytes() {
+                match byte? {
+                    SENTINEL_BYTE => {
+                        bwt.set(i, 0);
+                        sentinel = i;
+                    }
+                    char_i => bwt.set(i, char_i)
+                }
+
+                i += 1;
+            }
+
+            read_positions(&temp_dir.path(b, "samples"), &mut sparse_sa)?;
+        }
+
+        let sparse_sa = SparseSuffixArray {
+            bitvector:         B::from_bitvec(bitvector),
+            sparse_sa:         sparse_sa,
+            sparse_isa:        sparse_isa,
+            sparseness_factor: sparseness_factor
+        };
+
+        // The text is only read into memory when the index keeps it
+        let packed_text = if options.self_index {
+            None
+        } else {
+            Some(text.read(&memory)?)
+        };
+
+        memory.free(text.size_in_bytes());
+        drop(text);
+
+        let index = Self::from_parts(packed_text, bwt, sentinel, sparse_sa, &memory)?;
+        index.to_bin(output)?;
+
+        return Ok(memory.stats());
+    }
+
+    /// Choose the length of the prefixes the suffixes are distributed by, and group the prefixes
+    /// into blocks that can be sorted in memory
+    fn plan_blocks(
+        text: &TextFile<A>,
+        options: &ConstructionOptions,
+        memory: &MemoryTracker
+    ) -> Result<(usize, Vec<Block>)> {
+        let base = text.alphabet.len() + 1;
+
+        let mut prefix_length = 1;
+        loop {
+            let buckets = base.pow(prefix_length as u32);
+            memory.allocate(buckets * mem::size_of::<usize>())?;
+
+            // Count the suffixes per prefix
+            let mut histogram = vec![0; buckets];
+            let mut keys = PrefixKeys::new(text, prefix_length);
+            for _ in 0 ..= text.len() {
+                histogram[keys.next_key()] += 1;
+            }
+
+            let largest = *histogram.iter().max().unwrap();
+
+            // The space left for a block, the histogram is freed before the blocks are sorted
+            let capacity = match (options.block_size, options.memory_budget) {
+                (Some(block_size), _) => block_size,
+                (None, Some(budget)) => {
+                    budget.saturating_sub(memory.current()) / mem::size_of::<T>()
+                }
+                (None, None) => DEFAULT_BLOCK_SIZE
+            };
+
+            // Longer prefixes split the buckets further, unless there are too many buckets
+            if largest > capacity && buckets * base <= MAX_BUCKETS {
+                memory.free(buckets * mem::size_of::<usize>());
+                prefix_length += 1;
+                continue;
+            }
+
+            if largest > capacity && options.block_size.is_none() {
+                if let Some(budget) = options.memory_budget {
+                    bail!(ErrorKind::MemoryBudgetExceeded(
+                        memory.current() + largest * mem::size_of::<T>(),
+                        budget
+                    ));
+                }
+            }
+
+            // Group consecutive prefixes, a single prefix may exceed the capacity
+            let mut blocks: Vec<Block> = vec![];
+            for (key, count) in histogram.iter().enumerate() {
+                match blocks.last_mut() {
+                    Some(block) if block.size + count <= capacity => {
+                        block.keys.end = key + 1;
+                        block.size += count;
+                    }
+                    _ => blocks.push(Block {
+                        keys: key .. key + 1,
+                        size: *count
+                    })
+                }
+            }
+
+            memory.free(buckets * mem::size_of::<usize>());
+
+            return Ok((prefix_length, blocks));
+        }
+    }
+}
+
+/// Read the positions in a temporary file
+fn read_positions<T: TextPosition>(path: &Path, positions: &mut Vec<T>) -> Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut value = [0; 8];
+    while !reader.fill_buf()?.is_empty() {
+        reader.read_exact(&mut value)?;
+        positions.push(T::from_usize(u64::from_le_bytes(value) as usize));
+    }
+
+    return Ok(());
+}
+
+/// Code of a character in a prefix, the sentinel and everything after it is 0
+fn code<A: Alphabet>(text: &TextFile<A>, i: usize) -> usize {
+    if i < text.len() {
+        return text.get(i) as usize + 1;
+    }
+
+    return 0;
+}
+
+// ======================================================================
+// == Blocks
+// ======================================================================
+
+/// Consecutive prefixes whose suffixes are sorted together
+#[derive(Debug)]
+struct Block {
+    /// The prefix keys in this block
+    keys: std::ops::Range<usize>,
+
+    /// The amount of suffixes in this block
+    size: usize
+}
+
+/// Rolling keys of the prefixes of all suffixes, in text order. Keys are ordered like the
+/// prefixes, the sentinel sorts first.
+struct PrefixKeys<'a, A: Alphabet> {
+    text: &'a TextFile<A>,
+
+    /// Length of the prefixes
+    prefix_length: usize,
+
+    /// Amount of distinct codes per character
+    base: usize,
+
+    /// Weight of the first character of a prefix
+    first_weight: usize,
+
+    /// Key of the next suffix
+    key: usize,
+
+    /// Start of the next suffix
+    p: usize
+}
+
+impl<'a, A: Alphabet> PrefixKeys<'a, A> {
+    fn new(text: &'a TextFile<A>, prefix_length: usize) -> Self {
+        let base = text.alphabet.len() + 1;
+
+        let mut keys = Self {
+            text:          text,
+            prefix_length: prefix_length,
+            base:          base,
+            first_weight:  base.pow(prefix_length as u32 - 1),
+            key:           0,
+            p:             0
+        };
+
+        for i in 0 .. prefix_length {
+            keys.key = keys.key * base + code(text, i);
+        }
+
+        return keys;
+    }
+
+    /// Get the key of the next suffix
+    fn next_key(&mut self) -> usize {
+        let key = self.key;
+
+        self.key = (self.key - code(self.text, self.p) * self.first_weight) * self.base
+            + code(self.text, self.p + self.prefix_length);
+        self.p += 1;
+
+        return key;
+    }
+}
+
+// ======================================================================
+// == Difference cover
+// ======================================================================
+
+/// Ranks of the suffixes starting at the positions of a difference cover modulo
+/// `COVER_PERIOD`. For any two positions there is an offset below the period at which both are
+/// sampled, so two suffixes are ordered by at most that many characters and the ranks after it.
+struct DifferenceCover<T: TextPosition> {
+    /// Sampled residues modulo the period, in increasing order
+    residues: Vec<usize>,
+
+    /// Index in `residues` of every residue, if it is sampled
+    indices: Vec<Option<usize>>,
+
+    /// Smallest offset after which the positions with two residues are both sampled
+    offsets: Vec<usize>,
+
+    /// Rank of the suffix at every sampled position
+    ranks: Vec<T>
+}
+
+impl<T: TextPosition> DifferenceCover<T> {
+    fn new<A: Alphabet>(text: &TextFile<A>, memory: &MemoryTracker) -> Result<Self> {
+        let n = text.len();
+
+        // Every difference is a multiple of the root minus a smaller number
+        let residues: Vec<usize> = (0 .. COVER_ROOT)
+            .chain((1 .. COVER_ROOT).map(|k| k * COVER_ROOT))
+            .collect();
+
+        let mut indices = vec![None; COVER_PERIOD];
+        for (i, residue) in residues.iter().enumerate() {
+            indices[*residue] = Some(i);
+        }
+
+        let mut offsets = vec![0; COVER_PERIOD * COVER_PERIOD];
+        for a in 0 .. COVER_PERIOD {
+            for b in 0 .. COVER_PERIOD {
+                offsets[a * COVER_PERIOD + b] = (0 .. COVER_PERIOD)
+                    .find(|k| {
+                        indices[(a + k) % COVER_PERIOD].is_some()
+                            && indices[(b + k) % COVER_PERIOD].is_some()
+                    })
+                    .expect("the residues form a difference cover");
+            }
+        }
+
+        let mut cover = Self {
+            residues: residues,
+            indices:  indices,
+            offsets:  offsets,
+            ranks:    vec![]
+        };
+        memory.allocate(cover.size_in_bytes())?;
+
+        let ranks_size = (n / COVER_PERIOD + 1) * cover.residues.len();
+        memory.allocate(ranks_size * mem::size_of::<T>())?;
+        let mut ranks = vec![T::zero(); ranks_size];
+
+        // Sort the sampled positions by their first `COVER_PERIOD` characters
+        let samples = cover.samples(n).count();
+        memory.allocate(samples * mem::size_of::<T>())?;
+
+        let mut positions: Vec<T> = cover.samples(n).map(T::from_usize).collect();
+        positions.sort_unstable_by(|a, b| compare_prefixes(text, a.as_usize(), b.as_usize()));
+
+        // Name the prefixes, equal prefixes get the same name
+        let mut name = 0;
+        for i in 0 .. samples {
+            let p = positions[i].as_usize();
+
+            if i == 0 || compare_prefixes(text, positions[i - 1].as_usize(), p) != Ordering::Equal {
+                name += 1;
+            }
+
+            ranks[cover.slot(p)] = T::from_usize(name);
+        }
+
+        // The samples of every residue in text order form the reduced text. The last sample of a
+        // residue contains the sentinel in its prefix, so its name is unique and comparisons
+        // never run into the next residue.
+        for (i, p) in cover.samples(n).enumerate() {
+            positions[i] = ranks[cover.slot(p)];
+        }
+
+        let sa = suffix_array::construct::<T, _>(&Names(&positions), name, memory)?;
+
+        for rank in 1 ..= samples {
+            positions[sa[rank].as_usize()] = T::from_usize(rank);
+        }
+
+        memory.free(sa.len() * mem::size_of::<T>());
+        drop(sa);
+
+        for (i, p) in cover.samples(n).enumerate() {
+            ranks[cover.slot(p)] = positions[i];
+        }
+
+        memory.free(samples * mem::size_of::<T>());
+
+        cover.ranks = ranks;
+
+        return Ok(cover);
+    }
+
+    /// Get the sampled positions up to and including `n`, grouped by residue
+    fn samples(&self, n: usize) -> impl Iterator<Item = usize> + '_ {
+        return self
+            .residues
+            .iter()
+            .flat_map(move |residue| (*residue ..= n).step_by(COVER_PERIOD));
+    }
+
+    /// Get the index of a sampled position in the ranks
+    fn slot(&self, p: usize) -> usize {
+        let index = self.indices[p % COVER_PERIOD].expect("the position is sampled");
+
+        return p / COVER_PERIOD * self.residues.len() + index;
+    }
+
+    /// Compare two suffixes of a text, a suffix sorts before its extensions
+    fn compare<A: Alphabet>(&self, text: &TextFile<A>, a: usize, b: usize) -> Ordering {
+        if a == b {
+            return Ordering::Equal;
+        }
+
+        let offset = self.offsets[a % COVER_PERIOD * COVER_PERIOD + b % COVER_PERIOD];
+
+        // The suffixes differ before either of them reaches the sentinel
+        for k in 0 .. offset {
+            match code(text, a + k).cmp(&code(text, b + k)) {
+                Ordering::Equal => (),
+                ordering => return ordering
+            }
+        }
+
+        return self.ranks[self.slot(a + offset)].cmp(&self.ranks[self.slot(b + offset)]);
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        return (self.residues.len() + self.indices.len() + self.offsets.len())
+            * mem::size_of::<usize>()
+            + self.ranks.len() * mem::size_of::<T>();
+    }
+}
+
+/// Compare the first `COVER_PERIOD` characters of two suffixes, the sentinel sorts first
+fn compare_prefixes<A: Alphabet>(text: &TextFile<A>, a: usize, b: usize) -> Ordering {
+    for k in 0 .. COVER_PERIOD {
+        match code(text, a + k).cmp(&code(text, b + k)) {
+            Ordering::Equal if a + k >= text.len() => return Ordering::Equal,
+            Ordering::Equal => (),
+            ordering => return ordering
+        }
+    }
+
+    return Ordering::Equal;
+}
+
+// ======================================================================
+// == TextFile
+// ======================================================================
+
+/// Packed text in a temporary file, read through a small cache of pages. The characters are
+/// packed like in a `PackedAlphabetString`.
+struct TextFile<A: Alphabet> {
+    alphabet: A,
+
+    /// Amount of characters
+    n: usize,
+
+    /// Amount of bits per character
+    bits: usize,
+
+    /// The temporary file holding the packed words
+    file: RefCell<File>,
+
+    /// Recently read pages with their index, the most recently used first
+    pages: RefCell<Vec<(usize, Vec<u64>)>>
+}
+
+impl<A: Alphabet> TextFile<A> {
+    /// Pack a text into the file at `path`, ignoring line breaks
+    fn write<R: Read>(input: R, alphabet: A, path: &Path, memory: &MemoryTracker) -> Result<Self> {
+        memory.allocate(CACHED_PAGES * PAGE_WORDS * mem::size_of::<u64>())?;
+
+        let bits = alphabet.bits();
+        let characters_per_word = 64 / bits;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        let mut word = 0;
+        let mut n = 0;
+        for byte in BufReader::new(input).bytes() {
+            let character = byte?;
+
+            if character == b'\n' || character == b'\r' {
+                continue;
+            }
+
+            match alphabet.try_c2i(character) {
+                Some(char_i) => word |= (char_i as u64) << (n % characters_per_word * bits),
+                None => bail!(ErrorKind::InvalidCharacter(n, character))
+            }
+
+            n += 1;
+
+            if n % characters_per_word == 0 {
+                writer.write_all(&word.to_le_bytes())?;
+                word = 0;
+            }
+        }
+
+        if n % characters_per_word != 0 {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+
+        writer.flush()?;
+        drop(writer);
+
+        return Ok(Self {
+            alphabet: alphabet,
+            n:        n,
+            bits:     bits,
+            file:     RefCell::new(File::open(path)?),
+            pages:    RefCell::new(Vec::with_capacity(CACHED_PAGES))
+        });
+    }
+
+    /// Get the character at position `i`
+    fn get(&self, i: usize) -> AlphabetIndex {
+        let characters_per_word = 64 / self.bits;
+        let word = self.word(i / characters_per_word);
+
+        return ((word >> (i % characters_per_word * self.bits)) & ((1 << self.bits) - 1))
+            as AlphabetIndex;
+    }
+
+    /// Get a packed word, reading its page if it is not cached
+    fn word(&self, w: usize) -> u64 {
+        let page = w / PAGE_WORDS;
+        let mut pages = self.pages.borrow_mut();
+
+        match pages.iter().position(|(index, _)| *index == page) {
+            Some(0) => (),
+            Some(k) => {
+                let cached = pages.remove(k);
+                pages.insert(0, cached);
+            }
+            None => {
+                let words = self
+                    .read_page(page)
+                    .expect("Unable to read the temporary text file");
+
+                pages.truncate(CACHED_PAGES - 1);
+                pages.insert(0, (page, words));
+            }
+        }
+
+        return pages[0].1[w % PAGE_WORDS];
+    }
+
+    /// Read a page of packed words from the file
+    fn read_page(&self, page: usize) -> Result<Vec<u64>> {
+        let words = self.n.div_ceil(64 / self.bits);
+        let start = page * PAGE_WORDS;
+
+        let mut bytes = vec![0; (min(start + PAGE_WORDS, words) - start) * mem::size_of::<u64>()];
+
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start((start * mem::size_of::<u64>()) as u64))?;
+        file.read_exact(&mut bytes)?;
+
+        return Ok(bytes
+            .chunks_exact(mem::size_of::<u64>())
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect());
+    }
+
+    /// Read the complete text into memory
+    fn read(&self, memory: &MemoryTracker) -> Result<PackedAlphabetString<A>> {
+        let mut text = PackedAlphabetString::with_alphabet(self.n, self.alphabet.clone());
+        memory.allocate(text.size_in_bytes())?;
+
+        for i in 0 .. self.n {
+            text.set(i, self.get(i));
+        }
+
+        return Ok(text);
+    }
+
+    /// Get the amount of characters
+    fn len(&self) -> usize {
+        return self.n;
+    }
+
+    /// Get the amount of bytes of the cached pages
+    fn size_in_bytes(&self) -> usize {
+        return CACHED_PAGES * PAGE_WORDS * mem::size_of::<u64>();
+    }
+}
+
+// ======================================================================
+// == TempDir
+// ======================================================================
+
+/// Amount of temporary directories created by this process
+static TEMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary directory that is removed with its contents when dropped
+struct TempDir {
+    path: PathBuf
+}
+
+impl TempDir {
+    fn new(parent: Option<PathBuf>) -> Result<Self> {
+        let id = TEMP_DIRS.fetch_add(1, atomic::Ordering::SeqCst);
+
+        let mut path = parent.unwrap_or_else(env::temp_dir);
+        path.push(format!("rust-fm-{}-{}", process::id(), id));
+
+        fs::create_dir_all(&path)?;
+
+        return Ok(Self {
+            path: path
+        });
+    }
+
+    /// Get the path of a temporary file of a block
+    fn path(&self, block: usize, name: &str) -> PathBuf {
+        return self.path.join(format!("{}.{}", block, name));
+    }
+
+    /// Get the path of a temporary file shared by all blocks
+    fn file(&self, name: &str) -> PathBuf {
+        return self.path.join(name);
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs,
+        io::{
+            BufReader,
+            BufWriter
+        }
+    };
+
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        alphabet::{
+            AlphabetPattern,
+            AlphabetString,
+            DNAAlphabet,
+            PackedAlphabetString
+        },
+        construction::{
+            ConstructionOptions,
+            MemoryTracker
+        },
+        errors::ErrorKind,
+        index::{
+            external::{
+                DifferenceCover,
+                TempDir,
+                TextFile,
+                COVER_PERIOD
+            },
+            fm_index::FMIndex
+        },
+        io::Binary,
+        testing::random_text
+    };
+
+    const INPUT: &str = "AACTAGGGCAATGTTCAACG";
+
+    fn build_external(input: &str, options: &ConstructionOptions) -> Vec<u8> {
+        let mut output = vec![];
+
+        FMIndex::<DNAAlphabet, u64>::build_external(
+            input.as_bytes(),
+            DNAAlphabet::default(),
+            3,
+            options,
+            BufWriter::new(&mut output)
+        )
+        .unwrap();
+
+        return output;
+    }
+
+    fn build_internal(input: &str) -> Vec<u8> {
+        let text: String = input.chars().filter(|c| *c != '\n').collect();
+        let index =
+            FMIndex::<DNAAlphabet, u64>::new_with_types(AlphabetString::from(text.as_str()), 3);
+
+        return bincode::serialize(&index).unwrap();
+    }
+
+    #[test]
+    fn test_build_external() {
+        let input = "AACTAGGGCA\nATGTTCAACG\n";
+
+        let output = build_external(input, &ConstructionOptions::default());
+        assert_eq!(output, build_internal(input));
+
+        let index = FMIndex::<DNAAlphabet, u64>::from_bin(BufReader::new(&output[..])).unwrap();
+
+        let mut result = index.exact_match(&mut AlphabetPattern::from("AAC"));
+        result.sort();
+        assert_eq!(result, vec![0, 16]);
+
+        // A self-index equals the index without its text
+        let options = ConstructionOptions {
+            self_index: true,
+            ..Default::default()
+        };
+        let output = build_external(input, &options);
+
+        let mut index = FMIndex::<DNAAlphabet, u64>::new_with_types(AlphabetString::from(INPUT), 3);
+        index.drop_text();
+        assert_eq!(output, bincode::serialize(&index).unwrap());
+    }
+
+    #[test]
+    fn test_build_external_blocks() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut input = random_text(&mut rng, 5_000);
+
+        // Long repeats make buckets that can not be split
+        input.push_str(&"A".repeat(300));
+        input.push_str(&"CA".repeat(300));
+
+        let temp_dir = env::temp_dir().join("rust-fm-test-build-external-blocks");
+
+        for block_size in [7, 100, 1_000] {
+            let options = ConstructionOptions {
+                temp_dir: Some(temp_dir.clone()),
+                block_size: Some(block_size),
+                ..Default::default()
+            };
+
+            assert_eq!(build_external(&input, &options), build_internal(&input));
+        }
+
+        // The temporary files are removed
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 0);
+        fs::remove_dir(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_external_repeats() {
+        // Repeats longer than the period of the difference cover, ending in every residue
+        for length in [1, 255, 256, 257, 1_000] {
+            let input = ["A".repeat(length), "ACG".repeat(length), "A".repeat(length)].concat();
+
+            let options = ConstructionOptions {
+                block_size: Some(50),
+                ..Default::default()
+            };
+
+            assert_eq!(build_external(&input, &options), build_internal(&input));
+        }
+
+        let memory = MemoryTracker::new(None);
+        let temp_dir = TempDir::new(None).unwrap();
+        let text = TextFile::write(
+            "ACGT".as_bytes(),
+            DNAAlphabet::default(),
+            &temp_dir.file("text"),
+            &memory
+        )
+        .unwrap();
+        let cover = DifferenceCover::<u64>::new(&text, &memory).unwrap();
+
+        for a in 0 .. COVER_PERIOD {
+            for b in 0 .. COVER_PERIOD {
+                let offset = cover.offsets[a * COVER_PERIOD + b];
+
+                assert!(offset < COVER_PERIOD);
+                assert!(cover.indices[(a + offset) % COVER_PERIOD].is_some());
+                assert!(cover.indices[(b + offset) % COVER_PERIOD].is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_file() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let input = random_text(&mut rng, 1_000_000);
+        let expected = PackedAlphabetString::<DNAAlphabet>::from(input.as_str());
+
+        // Only a few pages of the packed text are held in memory
+        let memory = MemoryTracker::new(Some(expected.size_in_bytes() * 3 / 4));
+        let temp_dir = TempDir::new(None).unwrap();
+        let text = TextFile::write(
+            input.as_bytes(),
+            DNAAlphabet::default(),
+            &temp_dir.file("text"),
+            &memory
+        )
+        .unwrap();
+
+        assert_eq!(text.len(), input.len());
+
+        for _ in 0 .. 10_000 {
+            let i = rng.gen_range(0 .. input.len());
+            assert_eq!(text.get(i), expected.get(i));
+        }
+
+        // The complete text does not fit the budget
+        assert!(text.read(&memory).is_err());
+
+        let read = text.read(&MemoryTracker::new(None)).unwrap();
+        assert!(read.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn test_build_external_memory_budget() {
+        let mut rng = StdRng::seed_from_u64(12);
+
+        let input = random_text(&mut rng, 100_000);
+
+        let mut options = ConstructionOptions {
+            self_index: true,
+            block_size: Some(10_000),
+            ..Default::default()
+        };
+
+        let build = |options: &ConstructionOptions| {
+            let mut output = vec![];
+            let stats = FMIndex::<DNAAlphabet, u64>::build_external(
+                input.as_bytes(),
+                DNAAlphabet::default(),
+                3,
+                options,
+                BufWriter::new(&mut output)
+            )
+            .unwrap();
+
+            return (stats, output);
+        };
+
+        let (stats, expected) = build(&options);
+
+        // The text stays on disk, so a budget without room for it next to the finished index
+        // suffices
+        options.memory_budget = Some(stats.peak_memory);
+        let (_, output) = build(&options);
+        assert_eq!(output, expected);
+
+        let mut index =
+            FMIndex::<DNAAlphabet, u64>::new_with_types(AlphabetString::from(input.as_str()), 3);
+        index.drop_text();
+        assert_eq!(output, bincode::serialize(&index).unwrap());
+    }
+
+    #[test]
+    fn test_build_external_errors() {
+        let options = ConstructionOptions {
+            memory_budget: Some(64),
+            ..Default::default()
+        };
+
+        let result = FMIndex::<DNAAlphabet>::build_external(
+            INPUT.as_bytes(),
+            DNAAlphabet::default(),
+            3,
+            &options,
+            BufWriter::new(vec![])
+        );
+
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(..) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+
+        let result = FMIndex::<DNAAlphabet>::build_external(
+            "AACT\nAGXG".as_bytes(),
+            DNAAlphabet::default(),
+            3,
+            &ConstructionOptions::default(),
+            BufWriter::new(vec![])
+        );
+
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::InvalidCharacter(6, b'X') => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the invalid character was not detected")
+        }
+    }
+}
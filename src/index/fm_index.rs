@@ -1,4 +1,4 @@
-use std::fmt;
+use std::mem;
 
 use serde::{
     Deserialize,
@@ -10,98 +10,225 @@ use crate::{
         Alphabet,
         AlphabetPattern,
         AlphabetString,
+        AmbiguousPattern,
         DNAAlphabet,
-        Direction
+        Direction,
+        PackedAlphabetString
     },
-    bitvector::OccurenceTable,
-    matrix::BandedMatrix,
+    bitvector::{
+        Bitvec,
+        OccurenceTable,
+        Occurences,
+        RankSelect
+    },
+    construction::{
+        ConstructionOptions,
+        ConstructionStats,
+        MemoryTracker
+    },
+    errors::{
+        ErrorKind,
+        Result
+    },
+    index::{
+        self,
+        search_scheme::Distance,
+        Alignment,
+        AmbiguousMatch,
+        ApproximateMatch,
+        HammingMatch,
+        Locate,
+        Locator,
+        Strand
+    },
+    matrix::{
+        BandedMatrix,
+        EditMatrix
+    },
+    parallel,
     range::Range,
     suffix_array::{
+        self,
         SparseSuffixArray,
-        SuffixArray
+        TextPosition
     },
-    tree::{
-        Position,
-        SearchTree
-    }
+    tree::SearchTree
 };
 
 // ======================================================================
 // == FMIndex
 // ======================================================================
 
-/// FM index
+/// FM index, storing text positions as `T` and counting occurences with `O` and marking
+/// the sampled suffix array positions with `B`
 #[derive(Serialize, Deserialize, Debug)]
-pub struct FMIndex<A: Alphabet> {
-    /// The original text
-    text: AlphabetString<A>,
+pub struct FMIndex<
+    A: Alphabet,
+    T: TextPosition = u32,
+    O: Occurences = OccurenceTable,
+    B: RankSelect = Bitvec
+> {
+    /// The original text, left out of a self-index
+    text: Option<PackedAlphabetString<A>>,
 
-    /// Burrows Wheeler Transform of the original text
-    bwt: AlphabetString<A>,
+    /// The alphabet of the text
+    alphabet: A,
+
+    /// Length of the text
+    text_length: usize,
 
     /// Counts array
     counts: Vec<usize>,
 
     /// The sparse suffix array
-    sparse_sa: SparseSuffixArray,
+    sparse_sa: SparseSuffixArray<T, B>,
 
     /// occurence table
-    occurence_table: OccurenceTable
+    occurence_table: O
 }
 
 impl<A: Alphabet> FMIndex<A> {
-    /// construct a new FM index from a text
+    /// construct a new FM index from a text, with the default position type and backends
     pub fn new(text: AlphabetString<A>, sparseness_factor: u32) -> Self {
-        let text_length = text.len();
+        return Self::new_with_types(text, sparseness_factor);
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> FMIndex<A, T, O, B> {
+    /// construct a new FM index from a text, with the position type and backends of the index
+    pub fn new_with_types(text: AlphabetString<A>, sparseness_factor: u32) -> Self {
+        return Self::with_options(text, sparseness_factor, &ConstructionOptions::default())
+            .expect("construction without a memory budget can not fail")
+            .0;
+    }
+
+    /// Construct a new FM index from a text, reporting the peak memory usage
+    pub fn with_options(
+        text: AlphabetString<A>,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(Self, ConstructionStats)> {
+        return parallel::install(options.threads, || {
+            Self::build(text, sparseness_factor, options)
+        });
+    }
+
+    fn build(
+        text: AlphabetString<A>,
+        sparseness_factor: u32,
+        options: &ConstructionOptions
+    ) -> Result<(Self, ConstructionStats)> {
+        let memory = MemoryTracker::new(options.memory_budget);
+        memory.allocate(text.len())?;
 
         // Create the suffix array
-        let sa = SuffixArray::new(&text).into_parts().1;
+        let sa = suffix_array::construct::<T, _>(&text[..], text.alphabet.len(), &memory)?;
 
         // Create BWT from suffix array
-        let mut bwt = AlphabetString::<A>::new(text_length + 1);
-        let sentinel = Self::bwt_from_sa(&mut bwt, &sa, &text);
+        let (bwt, sentinel) = Self::bwt_from_sa(&sa, &text);
+        memory.allocate(bwt.size_in_bytes())?;
+
+        // Sample the suffix array, the full suffix array is no longer needed after this
+        let sparse_sa = SparseSuffixArray::from_sa(&sa, sparseness_factor);
+        memory.allocate(sparse_sa.size_in_bytes())?;
+        memory.free(sa.len() * mem::size_of::<T>());
+        drop(sa);
+
+        let text = if options.self_index {
+            None
+        } else {
+            let text = PackedAlphabetString::from(&text);
+            memory.allocate(text.size_in_bytes())?;
+            Some(text)
+        };
+
+        let index = Self::from_parts(text, bwt, sentinel, sparse_sa, &memory)?;
+
+        return Ok((index, memory.stats()));
+    }
 
+    /// Assemble an FM index from the packed text, the BWT and the sparse suffix array, a
+    /// self-index is assembled without the text
+    pub(crate) fn from_parts(
+        text: Option<PackedAlphabetString<A>>,
+        bwt: PackedAlphabetString<A>,
+        sentinel: usize,
+        sparse_sa: SparseSuffixArray<T, B>,
+        memory: &MemoryTracker
+    ) -> Result<Self> {
         // Initialize the counts table
         let mut counts = vec![0; bwt.alphabet.len()];
         Self::initialize_counts(&mut counts, &bwt, sentinel);
 
         // Create the occurence table
-        let occurence_table = OccurenceTable::from_bwt(&bwt, sentinel);
+        let occurence_table = O::from_bwt(&bwt, sentinel);
+        memory.allocate(occurence_table.size_in_bytes())?;
+
+        let alphabet = bwt.alphabet.clone();
+        let text_length = bwt.len() - 1;
+
+        // The occurence table gives access to the BWT
+        memory.free(bwt.size_in_bytes());
+        drop(bwt);
 
-        FMIndex {
+        return Ok(FMIndex {
             text:            text,
-            bwt:             bwt,
+            alphabet:        alphabet,
+            text_length:     text_length,
             counts:          counts,
-            sparse_sa:       SparseSuffixArray::from_sa(&sa, sparseness_factor),
+            sparse_sa:       sparse_sa,
             occurence_table: occurence_table
-        }
+        });
     }
 
     /// Construct the Burrows Wheeler Transformation from the suffix array
-    fn bwt_from_sa(bwt: &mut AlphabetString<A>, sa: &Vec<u32>, text: &AlphabetString<A>) -> usize {
-        let mut sentinel = 0;
-
-        for i in 0 .. sa.len() {
-            if sa[i] == 0 {
-                bwt[i] = 0;
-                sentinel = i;
-            } else {
-                bwt[i] = text[sa[i] as usize - 1];
+    fn bwt_from_sa(sa: &Vec<T>, text: &AlphabetString<A>) -> (PackedAlphabetString<A>, usize) {
+        let characters_per_word = 64 / text.alphabet.bits();
+
+        let parts = parallel::map_chunks(sa.len(), characters_per_word, |chunk| {
+            let mut bwt = PackedAlphabetString::with_alphabet(chunk.len(), text.alphabet.clone());
+            let mut sentinel = None;
+
+            for (i, value) in sa[chunk.clone()].iter().enumerate() {
+                if *value == T::zero() {
+                    bwt.set(i, 0);
+                    sentinel = Some(chunk.start + i);
+                } else {
+                    bwt.set(i, text[value.as_usize() - 1]);
+                }
             }
-        }
 
-        return sentinel;
+            return (bwt, sentinel);
+        });
+
+        let sentinel = parts
+            .iter()
+            .find_map(|(_, sentinel)| *sentinel)
+            .unwrap_or(0);
+        let parts = parts.into_iter().map(|(bwt, _)| bwt).collect();
+
+        return (PackedAlphabetString::concat(parts, text.alphabet.clone()), sentinel);
     }
 
     /// Construct the counts table
-    fn initialize_counts(counts: &mut Vec<usize>, bwt: &AlphabetString<A>, sentinel: usize) {
+    fn initialize_counts(counts: &mut Vec<usize>, bwt: &PackedAlphabetString<A>, sentinel: usize) {
         // Calculate counts
-        for (i, char_i) in bwt.iter().enumerate() {
-            if i == sentinel {
-                continue;
+        let chunk_counts = parallel::map_chunks(bwt.len(), 1, |chunk| {
+            let mut chunk_counts = vec![0; bwt.alphabet.len()];
+
+            for i in chunk {
+                if i != sentinel {
+                    chunk_counts[bwt.get(i) as usize] += 1;
+                }
             }
 
-            counts[(*char_i) as usize] += 1;
+            return chunk_counts;
+        });
+
+        for chunk_counts in chunk_counts {
+            for (char_i, count) in chunk_counts.into_iter().enumerate() {
+                counts[char_i] += count;
+            }
         }
 
         // Calculate the cumulative sum
@@ -113,26 +240,97 @@ impl<A: Alphabet> FMIndex<A> {
         }
     }
 
+    /// Get the alphabet of the indexed text
+    pub fn alphabet(&self) -> &A {
+        return &self.alphabet;
+    }
+
+    /// Get the length of the indexed text
+    pub fn text_length(&self) -> usize {
+        return self.text_length;
+    }
+
+    /// Check whether the original text is left out of the index
+    pub fn is_self_index(&self) -> bool {
+        return self.text.is_none();
+    }
+
+    /// Drop the original text, substrings are extracted from the BWT afterwards
+    pub fn drop_text(&mut self) {
+        self.text = None;
+    }
+
+    /// Get the range over the suffix array that matches the empty string
+    pub fn full_range(&self) -> Range<usize> {
+        return Range::new(0, self.text_length + 1);
+    }
+
     /// Find the previous character using the LF property
     fn find_lf(&self, k: usize) -> usize {
-        if k == self.occurence_table.sentinel {
+        if k == self.occurence_table.sentinel() {
             return 0;
         }
 
-        let char_i = self.bwt[k] as usize;
+        let char_i = self.occurence_table.symbol(k) as usize;
         return self.counts[char_i] + self.occurence_table.occ(char_i, k);
     }
 
     /// Find the correct position in the original text
-    fn find_sa(&self, k: usize) -> u32 {
+    pub(crate) fn find_sa(&self, k: usize) -> T {
         let mut i = k;
         let mut j = 0;
-        while !self.sparse_sa.contains(i as u32) {
+        while !self.sparse_sa.contains(i) {
             i = self.find_lf(i);
             j += 1;
         }
 
-        return self.sparse_sa[i] + j;
+        return self.sparse_sa[i] + T::from_usize(j);
+    }
+
+    /// Align a pattern to the text span of one of its approximate occurences, producing a CIGAR
+    /// string under `distance`. The pattern is aligned in its original order, whatever its
+    /// direction.
+    pub fn align(
+        &self,
+        pattern: &AlphabetPattern<A>,
+        occurence: &ApproximateMatch<T>,
+        distance: Distance
+    ) -> Result<Alignment<T>> {
+        let text = self.extract(occurence.position.as_usize(), occurence.length)?;
+
+        return index::align(pattern, &text, occurence, distance);
+    }
+
+    /// Extract `length` characters of the original text starting at `start`. Without the text,
+    /// they are reconstructed by LF mapping from the first sampled position after them.
+    pub fn extract(&self, start: usize, length: usize) -> Result<AlphabetString<A>> {
+        if start > self.text_length || length > self.text_length - start {
+            bail!(ErrorKind::InvalidRange(start, length, self.text_length));
+        }
+
+        let mut result = AlphabetString::with_alphabet(length, self.alphabet.clone());
+
+        if let Some(text) = &self.text {
+            for i in 0 .. length {
+                result[i] = text.get(start + i);
+            }
+
+            return Ok(result);
+        }
+
+        // The row of position `p` holds the character at `p - 1` in the BWT
+        let (position, mut row) = self.sparse_sa.next_isa_sample(start + length);
+        for p in (start .. position).rev() {
+            let char_i = self.occurence_table.symbol(row);
+
+            if p < start + length {
+                result[p - start] = char_i;
+            }
+
+            row = self.counts[char_i as usize] + self.occurence_table.occ(char_i as usize, row);
+        }
+
+        return Ok(result);
     }
 
     /// Try to add a character to the left
@@ -148,59 +346,235 @@ impl<A: Alphabet> FMIndex<A> {
         return !new_range.empty();
     }
 
-    /// Perform an exact match for a given pattern
-    pub fn exact_match(&self, pattern: &mut AlphabetPattern<A>) -> Vec<u32> {
-        let mut result = vec![];
-
-        let mut range = Range::new(0, self.text.len() + 1);
+    /// Find the range over the suffix array that matches a pattern, the range is empty if the
+    /// pattern does not occur
+    pub fn backward_search(&self, pattern: &mut AlphabetPattern<A>) -> Range<usize> {
+        let mut range = self.full_range();
 
         pattern.set_direction(Direction::BACKWARD);
 
         for i in 0 .. pattern.len() {
             if !self.add_char_left(pattern[i] as usize, &range.clone(), &mut range) {
-                return result;
+                break;
             }
         }
 
-        for i in range.start .. range.end {
-            result.push(self.find_sa(i));
+        return range;
+    }
+
+    /// Count the occurences of a pattern without locating them
+    pub fn count(&self, pattern: &mut AlphabetPattern<A>) -> usize {
+        return self.backward_search(pattern).width();
+    }
+
+    /// Locate the occurences of a pattern lazily
+    pub fn locate(&self, pattern: &mut AlphabetPattern<A>) -> Locate<'_, Self, T> {
+        return self.locate_range(self.backward_search(pattern));
+    }
+
+    /// Locate the occurences in a range over the suffix array lazily
+    pub fn locate_range(&self, range: Range<usize>) -> Locate<'_, Self, T> {
+        return Locate::new(self, range);
+    }
+
+    /// Perform an exact match for a given pattern
+    pub fn exact_match(&self, pattern: &mut AlphabetPattern<A>) -> Vec<T> {
+        return self.locate(pattern).collect();
+    }
+
+    /// Perform an exact match for a pattern and its reverse complement
+    pub fn exact_match_both_strands(
+        &self,
+        pattern: &mut AlphabetPattern<A>
+    ) -> Result<Vec<(Strand, T)>> {
+        let mut reverse_complement = match pattern.reverse_complement() {
+            Some(reverse_complement) => reverse_complement,
+            None => bail!(ErrorKind::MissingComplement)
+        };
+
+        let mut result: Vec<(Strand, T)> = self
+            .exact_match(pattern)
+            .into_iter()
+            .map(|position| (Strand::FORWARD, position))
+            .collect();
+
+        result.extend(
+            self.exact_match(&mut reverse_complement)
+                .into_iter()
+                .map(|position| (Strand::REVERSE, position))
+        );
+
+        return Ok(result);
+    }
+
+    /// Perform an exact match for a pattern with ambiguous characters, matching every concrete
+    /// sequence the pattern stands for. Unlike `exact_match`, which follows a single range, every
+    /// concrete sequence that occurs is reported with its own range.
+    pub fn ambiguous_exact_match(
+        &self,
+        pattern: &mut AmbiguousPattern<A>
+    ) -> Vec<AmbiguousMatch<A, Range<usize>>> {
+        let mut matches = vec![];
+
+        pattern.set_direction(Direction::BACKWARD);
+
+        // Partial matches: the current range and the characters added so far
+        let mut stack = vec![(self.full_range(), vec![])];
+
+        while let Some((range, characters)) = stack.pop() {
+            let i = characters.len();
+
+            if i == pattern.len() {
+                // Characters were added from right to left
+                let mut sequence = AlphabetString::with_alphabet(i, self.alphabet().clone());
+                for (j, char_i) in characters.iter().rev().enumerate() {
+                    sequence[j] = *char_i;
+                }
+
+                matches.push(AmbiguousMatch {
+                    sequence,
+                    range
+                });
+
+                continue;
+            }
+
+            let mut new_range = range;
+            for char_i in pattern[i].iter() {
+                if self.add_char_left(*char_i as usize, &range, &mut new_range) {
+                    let mut new_characters = characters.clone();
+                    new_characters.push(*char_i);
+
+                    stack.push((new_range, new_characters));
+                }
+            }
         }
 
-        return result;
+        // Different sequences have disjoint ranges, this sorts them lexicographically
+        matches.sort_by_key(|m| m.range.start);
+
+        return matches;
     }
 
-    /// Perform an approximate match for a given pattern
-    pub fn approximate_match(&self, pattern: &mut AlphabetPattern<A>, k: usize) -> Vec<Position> {
-        let mut occurences: Vec<Position> = vec![];
+    /// Perform an approximate match for a given pattern, allowing at most `k` substitutions.
+    /// Branches are pruned as soon as they exceed `k` mismatches.
+    pub fn hamming_match(
+        &self,
+        pattern: &mut AlphabetPattern<A>,
+        k: usize
+    ) -> Vec<HammingMatch<T>> {
+        let mut occurences = vec![];
 
         pattern.set_direction(Direction::BACKWARD);
 
-        let mut matrix = BandedMatrix::new(pattern.len(), k);
+        let m = pattern.len();
+
+        // Partial matches: the current range, the amount of characters added so far and the
+        // mismatched pattern positions, from right to left
+        let mut stack = vec![(self.full_range(), 0, vec![])];
+
+        while let Some((range, i, mismatches)) = stack.pop() {
+            if i == m {
+                let mismatches: Vec<usize> = mismatches.into_iter().rev().collect();
+
+                occurences.extend(self.locate_range(range).map(|position| HammingMatch {
+                    position:   position,
+                    mismatches: mismatches.clone()
+                }));
+
+                continue;
+            }
+
+            let mut new_range = range;
+            for char_i in 0 .. self.alphabet.len() {
+                let mismatch = char_i != pattern[i] as usize;
+
+                if mismatch && mismatches.len() == k {
+                    continue;
+                }
+
+                if self.add_char_left(char_i, &range, &mut new_range) {
+                    let mut new_mismatches = mismatches.clone();
+                    if mismatch {
+                        new_mismatches.push(m - i - 1);
+                    }
+
+                    stack.push((new_range, i + 1, new_mismatches));
+                }
+            }
+        }
+
+        occurences.sort_by_key(|occurence| occurence.position);
+
+        return occurences;
+    }
+
+    /// Perform an approximate match for a given pattern, allowing at most `k` edits. Every
+    /// occurence is reported once, with the shortest alignment of the fewest edits at its start
+    /// position. Alignments that overlap an alignment with fewer edits are left out.
+    pub fn approximate_match(
+        &self,
+        pattern: &mut AlphabetPattern<A>,
+        k: usize
+    ) -> Vec<ApproximateMatch<T>> {
+        return self.approximate_match_with::<BandedMatrix>(pattern, k);
+    }
+
+    /// Perform an approximate match like `approximate_match`, computing the edit distances with
+    /// `M`. `MyersMatrix` fills a row in O(m / 64) instead of O(k).
+    pub fn approximate_match_with<M: EditMatrix>(
+        &self,
+        pattern: &mut AlphabetPattern<A>,
+        k: usize
+    ) -> Vec<ApproximateMatch<T>> {
+        let mut occurences: Vec<ApproximateMatch<T>> = vec![];
+
+        if pattern.len() == 0 {
+            return occurences;
+        }
+
+        pattern.set_direction(Direction::BACKWARD);
+
+        // An occurence is at most k characters longer than the pattern
+        let max_length = pattern.len() + k;
+
+        let mut matrix = M::with_pattern(pattern, k);
 
         let mut search_tree = SearchTree::new(self);
 
-        search_tree.extend_search_space(&Range::new(0, self.text.len() + 1), 0);
+        search_tree.extend_search_space(&self.full_range(), 0);
 
         while let Some(item) = search_tree.next() {
             let min_edit_distance = matrix.update_row(&pattern, item.row(), item.character());
 
-            if min_edit_distance < k {
+            if min_edit_distance <= k && item.row() < max_length {
                 search_tree.extend_search_space(item.range(), item.row());
             }
 
             if matrix.in_final_column(item.row()) {
-                let value = matrix.final_column(item.row());
-
-                println!("VALUE: {:?}", value);
-
-                if value <= k {
-                    occurences.push(item);
+                let distance = matrix.final_column(item.row());
+
+                if distance <= k {
+                    occurences.extend(self.locate_range(*item.range()).map(|position| {
+                        ApproximateMatch {
+                            position: position,
+                            length:   item.row(),
+                            distance: distance
+                        }
+                    }));
                 }
             }
         }
 
-        // TODO: test and filter redundant matches
-        return occurences;
+        return index::filter_redundant(occurences, max_length);
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Locator<T>
+    for FMIndex<A, T, O, B>
+{
+    fn find_sa(&self, k: usize) -> T {
+        return FMIndex::find_sa(self, k);
     }
 }
 
@@ -210,16 +584,52 @@ impl<A: Alphabet> FMIndex<A> {
 
 #[cfg(test)]
 mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
     use crate::{
         alphabet::{
             Alphabet,
             AlphabetChar,
+            AlphabetIndex,
             AlphabetPattern,
             AlphabetString,
-            DNAAlphabet
+            AmbiguousPattern,
+            CustomAlphabet,
+            DNAAlphabet,
+            PackedAlphabetString,
+            ProteinAlphabet
         },
-        index::fm_index::FMIndex,
-        suffix_array::SuffixArray
+        bitvector::{
+            InterleavedOccurenceTable,
+            OccurenceTable
+        },
+        construction::ConstructionOptions,
+        errors::ErrorKind,
+        index::{
+            fm_index::FMIndex,
+            search_scheme::Distance,
+            ApproximateMatch,
+            Strand
+        },
+        matrix::CigarOperation,
+        myers::MyersMatrix,
+        range::Range,
+        rrr::RRRBitvec,
+        suffix_array::SuffixArray,
+        testing::{
+            mutate,
+            naive_hamming,
+            random_text,
+            substitute
+        },
+        wavelet_tree::{
+            BalancedWaveletTree,
+            HuffmanWaveletTree
+        }
     };
 
     const INPUT: &str = "AACTAGGGCAATGTTCAACG";
@@ -236,6 +646,10 @@ mod tests {
     ];
     const BWT_DOLLAR_POS: usize = 2;
 
+    const PROTEIN_INPUT: &str = "MKWVTFISLLFLFSSAYSRGVFRRDTHKSE";
+    const PROTEIN_BWT: &str = "ESRSTLVLRTFHMLFSARSFSKIYFVDGWKA";
+    const PROTEIN_BWT_DOLLAR_POS: usize = 16;
+
     #[test]
     fn test_bwt_from_sa() {
         let translated_input_vec = AlphabetString::<DNAAlphabet>::from(INPUT);
@@ -243,8 +657,12 @@ mod tests {
 
         let suffix_array = SuffixArray::new(&INPUT_VEC.to_vec()).into_parts().1;
 
-        let mut bwt = AlphabetString::new(21);
-        FMIndex::<DNAAlphabet>::bwt_from_sa(&mut bwt, &suffix_array, &translated_input_vec);
+        let (packed_bwt, sentinel) =
+            FMIndex::<DNAAlphabet>::bwt_from_sa(&suffix_array, &translated_input_vec);
+
+        let bwt: Vec<AlphabetIndex> = packed_bwt.iter().collect();
+
+        assert_eq!(sentinel, BWT_DOLLAR_POS);
 
         assert_eq!(bwt[0 .. BWT_DOLLAR_POS], translated_bwt_vec[0 .. BWT_DOLLAR_POS]);
         assert_eq!(bwt[BWT_DOLLAR_POS + 1 .. 21], translated_bwt_vec[BWT_DOLLAR_POS + 1 .. 21]);
@@ -252,7 +670,7 @@ mod tests {
 
     #[test]
     fn test_initialize_counts() {
-        let translated_bwt_vec = AlphabetString::<DNAAlphabet>::from(BWT);
+        let translated_bwt_vec = PackedAlphabetString::<DNAAlphabet>::from(BWT);
 
         let mut counts = vec![0; DNAAlphabet::default().len()];
         FMIndex::<DNAAlphabet>::initialize_counts(&mut counts, &translated_bwt_vec, BWT_DOLLAR_POS);
@@ -262,6 +680,23 @@ mod tests {
         assert_eq!(counts, counts_results);
     }
 
+    #[test]
+    fn test_initialize_counts_protein() {
+        let translated_bwt_vec = PackedAlphabetString::<ProteinAlphabet>::from(PROTEIN_BWT);
+
+        let mut counts = vec![0; ProteinAlphabet::default().len()];
+        FMIndex::<ProteinAlphabet>::initialize_counts(
+            &mut counts,
+            &translated_bwt_vec,
+            PROTEIN_BWT_DOLLAR_POS
+        );
+
+        let counts_results: [usize; 20] =
+            [1, 2, 2, 3, 4, 8, 9, 10, 11, 13, 16, 17, 17, 17, 17, 20, 25, 27, 29, 30];
+
+        assert_eq!(counts, counts_results);
+    }
+
     #[test]
     fn test_find_lf() {
         let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
@@ -286,6 +721,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_sa_u64() {
+        let fm_index = FMIndex::<DNAAlphabet, u64>::new_with_types(AlphabetString::from(INPUT), 3);
+
+        let sa_results: Vec<u64> =
+            vec![20, 16, 0, 9, 17, 1, 4, 10, 15, 8, 18, 2, 19, 7, 6, 5, 12, 3, 14, 11, 13];
+
+        for i in 0 .. BWT_VEC.len() {
+            assert_eq!(fm_index.find_sa(i), sa_results[i]);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_u64() {
+        let fm_index = FMIndex::<DNAAlphabet, u64>::new_with_types(AlphabetString::from(INPUT), 3);
+
+        let patterns = vec!["A", "AC", "AACT", "AACG", "CCC"];
+
+        let results: Vec<Vec<u64>> =
+            vec![vec![0, 1, 4, 9, 10, 16, 17], vec![1, 17], vec![0], vec![16], vec![]];
+
+        for i in 0 .. patterns.len() {
+            let mut result = fm_index.exact_match(&mut AlphabetPattern::from(patterns[i]));
+            result.sort();
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
     #[test]
     fn test_exact_match() {
         let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
@@ -341,21 +805,663 @@ mod tests {
         assert_eq!(fm_index.exact_match(&mut exact_match_not), exact_match_not_results);
     }
 
-    //    #[test]
-    //    fn test_approximate_match() {
-    //        let fm_index = FMIndex::new(INPUT_VEC.to_vec(), DNAAlphabet::default(), 3);
-    //
-    //        let pattern: Vec<AlphabetChar> = vec![b'C', b'T', b'A', b'G', b'G', b'T'];
-    //
-    //        let res = fm_index.approximate_match(&pattern, 1);
-    //
-    //        println!("{:?}", res);
-    //
-    //        assert_eq!(true, false);
-    //    }
-
-    // const INPUT_VEC: [AlphabetChar; 20] = [
-    //     b'A', b'A', b'C', b'T', b'A', b'G', b'G', b'G', b'C', b'A', b'A', b'T', b'G', b'T', b'T',
-    //     b'C', b'A', b'A', b'C', b'G'
-    // ];
+    #[test]
+    fn test_exact_match_protein() {
+        let fm_index = FMIndex::new(AlphabetString::<ProteinAlphabet>::from(PROTEIN_INPUT), 3);
+
+        let mut patterns = vec![
+            AlphabetPattern::<ProteinAlphabet>::from("S"),
+            AlphabetPattern::<ProteinAlphabet>::from("SL"),
+            AlphabetPattern::<ProteinAlphabet>::from("RR"),
+            AlphabetPattern::<ProteinAlphabet>::from("FLF"),
+            AlphabetPattern::<ProteinAlphabet>::from("KSE"),
+            AlphabetPattern::<ProteinAlphabet>::from("W"),
+            AlphabetPattern::<ProteinAlphabet>::from("C"),
+        ];
+
+        let results: Vec<Vec<u32>> =
+            vec![vec![7, 13, 14, 17, 28], vec![7], vec![22], vec![10], vec![27], vec![2], vec![]];
+
+        for i in 0 .. patterns.len() {
+            let mut result = fm_index.exact_match(&mut patterns[i]);
+            result.sort();
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_protein_wavelet_tree() {
+        let balanced = FMIndex::<ProteinAlphabet, u32, BalancedWaveletTree>::new_with_types(
+            AlphabetString::from(PROTEIN_INPUT),
+            3
+        );
+        let huffman = FMIndex::<ProteinAlphabet, u32, HuffmanWaveletTree>::new_with_types(
+            AlphabetString::from(PROTEIN_INPUT),
+            3
+        );
+
+        let patterns = vec!["S", "SL", "RR", "FLF", "KSE", "W", "C"];
+
+        let results: Vec<Vec<u32>> =
+            vec![vec![7, 13, 14, 17, 28], vec![7], vec![22], vec![10], vec![27], vec![2], vec![]];
+
+        for i in 0 .. patterns.len() {
+            let mut result = balanced.exact_match(&mut AlphabetPattern::from(patterns[i]));
+            result.sort();
+
+            assert_eq!(result, results[i]);
+
+            let mut result = huffman.exact_match(&mut AlphabetPattern::from(patterns[i]));
+            result.sort();
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_custom_alphabet() {
+        let alphabet = CustomAlphabet::from_specification("ACGT U=T", true).unwrap();
+
+        let fm_index =
+            FMIndex::new(AlphabetString::from_alphabet(&INPUT.to_lowercase(), alphabet.clone()), 3);
+
+        let mut exact_match_start =
+            AlphabetPattern::from(AlphabetString::from_alphabet("aacu", alphabet.clone()));
+        let mut exact_match_end =
+            AlphabetPattern::from(AlphabetString::from_alphabet("AACG", alphabet.clone()));
+        let mut exact_match_not =
+            AlphabetPattern::from(AlphabetString::from_alphabet("CCC", alphabet.clone()));
+
+        assert_eq!(fm_index.exact_match(&mut exact_match_start), vec![0]);
+        assert_eq!(fm_index.exact_match(&mut exact_match_end), vec![16]);
+        assert_eq!(fm_index.exact_match(&mut exact_match_not), vec![]);
+    }
+
+    #[test]
+    fn test_exact_match_both_strands() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let patterns = vec!["GT", "CA", "TTG", "ACG"];
+
+        let results = vec![
+            vec![(Strand::FORWARD, 12), (Strand::REVERSE, 1), (Strand::REVERSE, 17)],
+            vec![(Strand::FORWARD, 8), (Strand::FORWARD, 15), (Strand::REVERSE, 11)],
+            vec![(Strand::REVERSE, 8), (Strand::REVERSE, 15)],
+            vec![(Strand::FORWARD, 17)],
+        ];
+
+        for i in 0 .. patterns.len() {
+            let mut pattern = AlphabetPattern::<DNAAlphabet>::from(patterns[i]);
+
+            let mut result = fm_index.exact_match_both_strands(&mut pattern).unwrap();
+            result.sort_by_key(|(strand, position)| (*strand == Strand::REVERSE, *position));
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_both_strands_without_complement() {
+        let fm_index = FMIndex::new(AlphabetString::<ProteinAlphabet>::from(PROTEIN_INPUT), 3);
+
+        let mut pattern = AlphabetPattern::<ProteinAlphabet>::from("SL");
+
+        assert!(fm_index.exact_match_both_strands(&mut pattern).is_err());
+    }
+
+    #[test]
+    fn test_ambiguous_exact_match() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let mut patterns = vec![
+            AmbiguousPattern::<DNAAlphabet>::from("AAY"),
+            AmbiguousPattern::<DNAAlphabet>::from("RAC"),
+            AmbiguousPattern::<DNAAlphabet>::from("NCG"),
+            AmbiguousPattern::<DNAAlphabet>::from("AAC"),
+            AmbiguousPattern::<DNAAlphabet>::from("CCN"),
+        ];
+
+        let results: Vec<Vec<(&str, (usize, usize))>> = vec![
+            vec![("AAC", (1, 3)), ("AAT", (3, 4))],
+            vec![("AAC", (1, 3))],
+            vec![("ACG", (4, 5))],
+            vec![("AAC", (1, 3))],
+            vec![],
+        ];
+
+        for i in 0 .. patterns.len() {
+            let matches = fm_index.ambiguous_exact_match(&mut patterns[i]);
+
+            assert_eq!(matches.len(), results[i].len());
+
+            for (m, (sequence, range)) in matches.iter().zip(results[i].iter()) {
+                assert_eq!(*m.sequence, *AlphabetString::<DNAAlphabet>::from(*sequence));
+                assert_eq!((m.range.start, m.range.end), *range);
+            }
+        }
+    }
+
+    /// Best alignment of every start position with at most `k` edits, without the alignments
+    /// that overlap an alignment with fewer edits
+    fn naive_approximate_match(
+        text: &[AlphabetIndex],
+        pattern: &[AlphabetIndex],
+        k: usize
+    ) -> Vec<(usize, usize, usize)> {
+        let (n, m) = (text.len(), pattern.len());
+
+        let mut matches = vec![];
+
+        for start in 0 .. n {
+            // Edit distances between the prefixes of the pattern and the current substring
+            let mut column: Vec<usize> = (0 ..= m).collect();
+            let mut best: Option<(usize, usize)> = None;
+
+            for end in start + 1 ..= n.min(start + m + k) {
+                let mut diagonal = column[0];
+                column[0] = end - start;
+
+                for j in 1 ..= m {
+                    let value = (diagonal + (pattern[j - 1] != text[end - 1]) as usize)
+                        .min(column[j] + 1)
+                        .min(column[j - 1] + 1);
+
+                    diagonal = column[j];
+                    column[j] = value;
+                }
+
+                if column[m] <= k && best.map_or(true, |(_, distance)| column[m] < distance) {
+                    best = Some((end - start, column[m]));
+                }
+            }
+
+            if let Some((length, distance)) = best {
+                matches.push((start, length, distance));
+            }
+        }
+
+        return matches
+            .iter()
+            .filter(|(start, length, distance)| {
+                !matches
+                    .iter()
+                    .any(|(other_start, other_length, other_distance)| {
+                        other_distance < distance
+                            && other_start < &(start + length)
+                            && start < &(other_start + other_length)
+                    })
+            })
+            .cloned()
+            .collect();
+    }
+
+    #[test]
+    fn test_approximate_match() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("AACG");
+
+        let result: Vec<(u32, usize, usize)> = fm_index
+            .approximate_match(&mut pattern, 0)
+            .iter()
+            .map(|m| (m.position, m.length, m.distance))
+            .collect();
+        assert_eq!(result, vec![(16, 4, 0)]);
+
+        let result: Vec<(u32, usize, usize)> = fm_index
+            .approximate_match(&mut pattern, 1)
+            .iter()
+            .map(|m| (m.position, m.length, m.distance))
+            .collect();
+        assert_eq!(result, vec![(0, 3, 1), (9, 4, 1), (16, 4, 0)]);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("CCCC");
+        assert_eq!(fm_index.approximate_match(&mut pattern, 1), vec![]);
+
+        // The pattern itself is shorter than the amount of edits
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("GC");
+        let text = AlphabetString::<DNAAlphabet>::from(INPUT);
+
+        let result: Vec<(usize, usize, usize)> = fm_index
+            .approximate_match(&mut pattern, 3)
+            .iter()
+            .map(|m| (m.position as usize, m.length, m.distance))
+            .collect();
+        assert_eq!(result, naive_approximate_match(&text, &[2, 1], 3));
+    }
+
+    #[test]
+    fn test_approximate_match_random() {
+        let mut rng = StdRng::seed_from_u64(21);
+
+        let string = random_text(&mut rng, 2_000);
+        let text = AlphabetString::<DNAAlphabet>::from(string.as_str());
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(string.as_str()), 4);
+
+        for _ in 0 .. 30 {
+            // Take a substring of the text and edit it at random
+            let length = rng.gen_range(5 ..= 12);
+            let start = rng.gen_range(0 .. text.len() - length);
+            let edits = rng.gen_range(0 ..= 2);
+
+            let pattern_string = mutate(&mut rng, &string[start .. start + length], edits);
+            let pattern = AlphabetString::<DNAAlphabet>::from(pattern_string.as_str());
+
+            for k in 0 ..= 2 {
+                let mut alphabet_pattern =
+                    AlphabetPattern::<DNAAlphabet>::from(pattern_string.as_str());
+
+                let result: Vec<(usize, usize, usize)> = fm_index
+                    .approximate_match(&mut alphabet_pattern, k)
+                    .iter()
+                    .map(|m| (m.position as usize, m.length, m.distance))
+                    .collect();
+
+                assert_eq!(result, naive_approximate_match(&text, &pattern, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_approximate_match_myers() {
+        let mut rng = StdRng::seed_from_u64(25);
+
+        let text = random_text(&mut rng, 2_000);
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 4);
+
+        for _ in 0 .. 30 {
+            // Patterns on both sides of a single word
+            let length =
+                [rng.gen_range(5 ..= 20), rng.gen_range(60 ..= 140)][rng.gen_range(0 .. 2)];
+            let start = rng.gen_range(0 .. text.len() - length);
+
+            let pattern = mutate(&mut rng, &text[start .. start + length], 2);
+
+            for k in 0 ..= 3 {
+                let mut pattern = AlphabetPattern::<DNAAlphabet>::from(pattern.as_str());
+
+                assert_eq!(
+                    fm_index.approximate_match_with::<MyersMatrix>(&mut pattern, k),
+                    fm_index.approximate_match(&mut pattern, k)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+        let (self_index, _) = FMIndex::<DNAAlphabet>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &ConstructionOptions {
+                self_index: true,
+                ..Default::default()
+            }
+        )
+        .unwrap();
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("AACG");
+
+        for index in [&fm_index, &self_index] {
+            let result: Vec<(u32, usize, String)> = index
+                .approximate_match(&mut pattern, 1)
+                .iter()
+                .map(|m| {
+                    let alignment = index.align(&pattern, m, Distance::EDIT).unwrap();
+                    (alignment.position, alignment.length, alignment.cigar.to_string())
+                })
+                .collect();
+            assert_eq!(
+                result,
+                vec![
+                    (0, 3, "3=1I".to_string()),
+                    (9, 4, "2=1X1=".to_string()),
+                    (16, 4, "4=".to_string())
+                ]
+            );
+        }
+
+        // AGGG is two edits away from AACG
+        let occurence = ApproximateMatch {
+            position: 4,
+            length:   4,
+            distance: 1
+        };
+        match fm_index.align(&pattern, &occurence, Distance::EDIT) {
+            Err(e) => match e.kind() {
+                ErrorKind::AlignmentFailed(4, 1) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the alignment did not fail")
+        }
+    }
+
+    #[test]
+    fn test_align_random() {
+        let mut rng = StdRng::seed_from_u64(24);
+
+        let string = random_text(&mut rng, 2_000);
+        let text = AlphabetString::<DNAAlphabet>::from(string.as_str());
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(string.as_str()), 4);
+
+        for _ in 0 .. 30 {
+            let length = rng.gen_range(5 ..= 12);
+            let start = rng.gen_range(0 .. text.len() - length);
+
+            let pattern_string = mutate(&mut rng, &string[start .. start + length], 2);
+            let pattern = AlphabetString::<DNAAlphabet>::from(pattern_string.as_str());
+            let mut alphabet_pattern =
+                AlphabetPattern::<DNAAlphabet>::from(pattern_string.as_str());
+
+            for occurence in fm_index.approximate_match(&mut alphabet_pattern, 2) {
+                let alignment = fm_index
+                    .align(&alphabet_pattern, &occurence, Distance::EDIT)
+                    .unwrap();
+
+                assert_eq!(alignment.cigar.distance(), occurence.distance);
+                assert_eq!(alignment.cigar.pattern_length(), pattern.len());
+                assert_eq!(alignment.cigar.text_length(), occurence.length);
+
+                // Replay the alignment over the pattern and the text span
+                let (mut i, mut j) = (0, occurence.position as usize);
+                for (operation, count) in alignment.cigar.operations() {
+                    for _ in 0 .. *count {
+                        match operation {
+                            CigarOperation::MATCH => assert_eq!(pattern[i], text[j]),
+                            CigarOperation::MISMATCH => assert_ne!(pattern[i], text[j]),
+                            _ => ()
+                        }
+
+                        if *operation != CigarOperation::DELETION {
+                            i += 1;
+                        }
+                        if *operation != CigarOperation::INSERTION {
+                            j += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hamming_match() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("AACG");
+
+        let result: Vec<(u32, Vec<usize>)> = fm_index
+            .hamming_match(&mut pattern, 0)
+            .into_iter()
+            .map(|m| (m.position, m.mismatches))
+            .collect();
+        assert_eq!(result, vec![(16, vec![])]);
+
+        let result: Vec<(u32, Vec<usize>)> = fm_index
+            .hamming_match(&mut pattern, 1)
+            .into_iter()
+            .map(|m| (m.position, m.mismatches))
+            .collect();
+        assert_eq!(result, vec![(0, vec![3]), (9, vec![2]), (16, vec![])]);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("CCCC");
+        assert_eq!(fm_index.hamming_match(&mut pattern, 1), vec![]);
+    }
+
+    #[test]
+    fn test_hamming_match_random() {
+        let mut rng = StdRng::seed_from_u64(22);
+
+        let text = random_text(&mut rng, 5_000);
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 4);
+
+        for _ in 0 .. 30 {
+            let length = rng.gen_range(6 ..= 16);
+            let start = rng.gen_range(0 .. text.len() - length);
+            let substitutions = rng.gen_range(0 ..= 2);
+
+            let pattern = substitute(&mut rng, &text[start .. start + length], substitutions);
+
+            for k in 0 ..= 3 {
+                let result: Vec<(usize, Vec<usize>)> = fm_index
+                    .hamming_match(&mut AlphabetPattern::from(pattern.as_str()), k)
+                    .into_iter()
+                    .map(|m| (m.position as usize, m.mismatches))
+                    .collect();
+
+                assert_eq!(result, naive_hamming(&text, &pattern, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_options() {
+        let (index, stats) = FMIndex::<DNAAlphabet, u64>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &ConstructionOptions::default()
+        )
+        .unwrap();
+
+        // At least the text and the suffix array were alive at the same time
+        assert!(stats.peak_memory >= INPUT.len() + (INPUT.len() + 1) * 8);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("AAC");
+        let mut result = index.exact_match(&mut pattern);
+        result.sort();
+        assert_eq!(result, vec![0, 16]);
+
+        // The peak memory is exactly the smallest budget that suffices
+        let options = ConstructionOptions {
+            memory_budget: Some(stats.peak_memory),
+            ..Default::default()
+        };
+        assert!(FMIndex::<DNAAlphabet, u64>::with_options(
+            AlphabetString::from(INPUT),
+            3,
+            &options
+        )
+        .is_ok());
+
+        let options = ConstructionOptions {
+            memory_budget: Some(stats.peak_memory - 1),
+            ..Default::default()
+        };
+        match FMIndex::<DNAAlphabet, u64>::with_options(AlphabetString::from(INPUT), 3, &options) {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(..) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+    }
+
+    #[test]
+    fn test_parallel() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let text = random_text(&mut rng, 10_000);
+
+        let (serial, _) = FMIndex::<DNAAlphabet>::with_options(
+            AlphabetString::from(text.as_str()),
+            4,
+            &ConstructionOptions::default()
+        )
+        .unwrap();
+
+        let options = ConstructionOptions {
+            threads: 4,
+            ..Default::default()
+        };
+        let (parallel, _) =
+            FMIndex::<DNAAlphabet>::with_options(AlphabetString::from(text.as_str()), 4, &options)
+                .unwrap();
+
+        assert_eq!(bincode::serialize(&serial).unwrap(), bincode::serialize(&parallel).unwrap());
+    }
+
+    #[test]
+    fn test_exact_match_interleaved() {
+        let mut rng = StdRng::seed_from_u64(12);
+
+        let text = random_text(&mut rng, 10_000);
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 3);
+        let interleaved = FMIndex::<DNAAlphabet, u32, InterleavedOccurenceTable>::new_with_types(
+            AlphabetString::from(text.as_str()),
+            3
+        );
+
+        for _ in 0 .. 100 {
+            let start = rng.gen_range(0 .. text.len() - 10);
+            let pattern = &text[start .. start + rng.gen_range(1 ..= 10)];
+
+            let mut expected = fm_index.exact_match(&mut AlphabetPattern::from(pattern));
+            let mut result = interleaved.exact_match(&mut AlphabetPattern::from(pattern));
+            expected.sort();
+            result.sort();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_rrr() {
+        let fm_index =
+            FMIndex::<DNAAlphabet, u32, OccurenceTable<RRRBitvec>, RRRBitvec>::new_with_types(
+                AlphabetString::from(INPUT),
+                3
+            );
+
+        let patterns = vec!["A", "AC", "AACT", "AACG", "CCC"];
+
+        let results: Vec<Vec<u32>> =
+            vec![vec![0, 1, 4, 9, 10, 16, 17], vec![1, 17], vec![0], vec![16], vec![]];
+
+        for i in 0 .. patterns.len() {
+            let mut result = fm_index.exact_match(&mut AlphabetPattern::from(patterns[i]));
+            result.sort();
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let options = ConstructionOptions {
+            self_index: true,
+            ..Default::default()
+        };
+
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+        let (self_index, _) =
+            FMIndex::<DNAAlphabet>::with_options(AlphabetString::from(INPUT), 3, &options).unwrap();
+
+        assert!(!fm_index.is_self_index());
+        assert!(self_index.is_self_index());
+
+        let text = AlphabetString::<DNAAlphabet>::from(INPUT);
+
+        for start in 0 ..= INPUT.len() {
+            for length in 0 ..= INPUT.len() - start {
+                let expected = &text[start .. start + length];
+
+                assert_eq!(*fm_index.extract(start, length).unwrap(), *expected);
+                assert_eq!(*self_index.extract(start, length).unwrap(), *expected);
+            }
+        }
+
+        match self_index.extract(15, 6) {
+            Err(e) => match e.kind() {
+                ErrorKind::InvalidRange(15, 6, 20) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the range was not checked")
+        }
+        assert!(self_index.extract(21, 0).is_err());
+        assert!(self_index.extract(1, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_extract_random() {
+        let mut rng = StdRng::seed_from_u64(18);
+
+        let text = random_text(&mut rng, 5_000);
+        let expected = AlphabetString::<DNAAlphabet>::from(text.as_str());
+
+        let mut fm_index = FMIndex::<DNAAlphabet, u64, HuffmanWaveletTree>::new_with_types(
+            AlphabetString::from(text.as_str()),
+            32
+        );
+        fm_index.drop_text();
+
+        assert_eq!(fm_index.text_length(), text.len());
+        assert!(fm_index.is_self_index());
+
+        for _ in 0 .. 100 {
+            let start = rng.gen_range(0 .. text.len());
+            let length = rng.gen_range(0 ..= (text.len() - start).min(100));
+
+            let result = fm_index.extract(start, length).unwrap();
+            assert_eq!(*result, expected[start .. start + length]);
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 3);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("A");
+
+        assert_eq!(fm_index.count(&mut pattern), 7);
+
+        let locate = fm_index.locate(&mut pattern);
+        assert_eq!(locate.range(), Range::new(1, 8));
+        assert_eq!(locate.len(), 7);
+
+        let mut result: Vec<u32> = locate.collect();
+        result.sort();
+        assert_eq!(result, vec![0, 1, 4, 9, 10, 16, 17]);
+
+        // The first hits are the first rows of the range
+        let locate = fm_index.locate(&mut pattern).max_hits(3);
+        assert_eq!(locate.len(), 3);
+        assert_eq!(locate.range().width(), 7);
+        assert_eq!(locate.collect::<Vec<u32>>(), vec![16, 0, 9]);
+
+        assert_eq!(fm_index.locate(&mut pattern).max_hits(10).len(), 7);
+        assert_eq!(fm_index.locate(&mut pattern).max_hits(0).next(), None);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("CCC");
+        assert_eq!(fm_index.count(&mut pattern), 0);
+        assert_eq!(fm_index.locate(&mut pattern).next(), None);
+
+        // An ambiguous match gives a range to locate
+        let mut pattern = AmbiguousPattern::<DNAAlphabet>::from("AAY");
+        let matches = fm_index.ambiguous_exact_match(&mut pattern);
+
+        let mut result: Vec<u32> = fm_index.locate_range(matches[0].range).collect();
+        result.sort();
+        assert_eq!(result, vec![0, 16]);
+    }
+
+    #[test]
+    fn test_locate_repetitive() {
+        let text = "A".repeat(100_000);
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text.as_str()), 32);
+
+        let mut pattern = AlphabetPattern::<DNAAlphabet>::from("AAAA");
+
+        assert_eq!(fm_index.count(&mut pattern), 99_997);
+
+        // The rows of a unary text are sorted by decreasing position
+        let result: Vec<u32> = fm_index.locate(&mut pattern).max_hits(5).collect();
+        assert_eq!(result, vec![99_996, 99_995, 99_994, 99_993, 99_992]);
+    }
 }
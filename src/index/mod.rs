@@ -1,2 +1,221 @@
 pub mod bidirectional_fm_index;
+pub mod collection;
+pub mod external;
 pub mod fm_index;
+pub mod r_index;
+pub mod search_scheme;
+
+use std::marker::PhantomData;
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetPattern,
+        AlphabetString,
+        Direction
+    },
+    errors::{
+        ErrorKind,
+        Result
+    },
+    index::search_scheme::Distance,
+    matrix::{
+        BandedMatrix,
+        Cigar
+    },
+    range::Range,
+    suffix_array::TextPosition
+};
+
+/// Concrete sequence that matched an ambiguous pattern, along with its range in the index
+#[derive(Debug)]
+pub struct AmbiguousMatch<A: Alphabet, R> {
+    /// The matched sequence, in pattern order
+    pub sequence: AlphabetString<A>,
+
+    /// The range of the sequence in the index
+    pub range: R
+}
+
+/// Occurence of a pattern in the text with at most a given amount of edits
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ApproximateMatch<T: TextPosition> {
+    /// Start of the occurence in the text
+    pub position: T,
+
+    /// Length of the occurence in the text
+    pub length: usize,
+
+    /// Edit distance between the pattern and the occurence
+    pub distance: usize
+}
+
+/// Keep the best alignment of every start position, with the fewest edits and then the shortest
+/// length, and remove the occurences that overlap an occurence with fewer edits. Occurences are
+/// at most `max_length` long.
+pub(crate) fn filter_redundant<T: TextPosition>(
+    mut occurences: Vec<ApproximateMatch<T>>,
+    max_length: usize
+) -> Vec<ApproximateMatch<T>> {
+    occurences.sort_by_key(|m| (m.position, m.distance, m.length));
+    occurences.dedup_by_key(|m| m.position);
+
+    let overlaps = |a: &ApproximateMatch<T>, b: &ApproximateMatch<T>| {
+        let (a_start, b_start) = (a.position.as_usize(), b.position.as_usize());
+        return a_start < b_start + b.length && b_start < a_start + a.length;
+    };
+
+    let mut result = vec![];
+
+    for (i, occurence) in occurences.iter().enumerate() {
+        let start = occurence.position.as_usize();
+
+        let preceding = occurences[.. i]
+            .iter()
+            .rev()
+            .take_while(|other| other.position.as_usize() + max_length > start);
+        let following = occurences[i + 1 ..]
+            .iter()
+            .take_while(|other| other.position.as_usize() < start + occurence.length);
+
+        let redundant = preceding
+            .chain(following)
+            .any(|other| other.distance < occurence.distance && overlaps(occurence, other));
+
+        if !redundant {
+            result.push(*occurence);
+        }
+    }
+
+    return result;
+}
+
+/// Alignment of a pattern to the text span of an approximate occurence
+#[derive(Clone, PartialEq, Debug)]
+pub struct Alignment<T: TextPosition> {
+    /// Start of the aligned text span (0-based, SAM positions are 1-based)
+    pub position: T,
+
+    /// Length of the aligned text span
+    pub length: usize,
+
+    /// The alignment operations, distinguishing matches from mismatches
+    pub cigar: Cigar
+}
+
+/// Align a pattern in its original order to `text`, the text span of `occurence`, under the
+/// distance the occurence was found with
+pub(crate) fn align<A: Alphabet, T: TextPosition>(
+    pattern: &AlphabetPattern<A>,
+    text: &AlphabetString<A>,
+    occurence: &ApproximateMatch<T>,
+    distance: Distance
+) -> Result<Alignment<T>> {
+    let pattern = pattern.slice(0, pattern.len(), Direction::FORWARD);
+
+    let cigar = match distance {
+        Distance::HAMMING => Cigar::hamming(&pattern, text, occurence.distance),
+        Distance::EDIT => BandedMatrix::align(&pattern, text, occurence.distance)
+    };
+
+    let cigar = match cigar {
+        Some(cigar) => cigar,
+        None => bail!(ErrorKind::AlignmentFailed(occurence.position.as_usize(), occurence.distance))
+    };
+
+    return Ok(Alignment {
+        position: occurence.position,
+        length:   occurence.length,
+        cigar:    cigar
+    });
+}
+
+/// Occurence of a pattern in the text with substitutions only
+#[derive(Clone, PartialEq, Debug)]
+pub struct HammingMatch<T: TextPosition> {
+    /// Start of the occurence in the text
+    pub position: T,
+
+    /// Positions in the pattern that differ from the text, in increasing order
+    pub mismatches: Vec<usize>
+}
+
+/// Strand of the text on which a match was found
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Strand {
+    /// The pattern itself occurs in the text
+    FORWARD,
+
+    /// The reverse complement of the pattern occurs in the text
+    REVERSE
+}
+
+/// Index that can find the text position of every row of its suffix array
+pub trait Locator<T: TextPosition> {
+    /// Find the text position of a row of the suffix array
+    fn find_sa(&self, k: usize) -> T;
+}
+
+/// Iterator over the text positions of a range over the suffix array, every position is only
+/// located when it is requested
+pub struct Locate<'a, I: Locator<T>, T: TextPosition> {
+    /// The index the positions are located in
+    index: &'a I,
+
+    /// The range over the suffix array
+    range: Range<usize>,
+
+    /// The next row to locate
+    row: usize,
+
+    /// The row after the last one to locate
+    end: usize,
+
+    /// The type of the located positions
+    position: PhantomData<T>
+}
+
+impl<'a, I: Locator<T>, T: TextPosition> Locate<'a, I, T> {
+    pub fn new(index: &'a I, range: Range<usize>) -> Self {
+        Self {
+            index:    index,
+            range:    range,
+            row:      range.start,
+            end:      range.start + range.width(),
+            position: PhantomData
+        }
+    }
+
+    /// Get the range over the suffix array, its width is the total amount of occurences
+    pub fn range(&self) -> Range<usize> {
+        return self.range;
+    }
+
+    /// Stop after locating at most `max_hits` more positions
+    pub fn max_hits(mut self, max_hits: usize) -> Self {
+        self.end = self.end.min(self.row.saturating_add(max_hits));
+        return self;
+    }
+}
+
+impl<'a, I: Locator<T>, T: TextPosition> Iterator for Locate<'a, I, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.row >= self.end {
+            return None;
+        }
+
+        let position = self.index.find_sa(self.row);
+        self.row += 1;
+
+        return Some(position);
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.row;
+        return (remaining, Some(remaining));
+    }
+}
+
+impl<'a, I: Locator<T>, T: TextPosition> ExactSizeIterator for Locate<'a, I, T> {}
@@ -0,0 +1,515 @@
+use std::{
+    collections::HashMap,
+    mem
+};
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetIndex,
+        AlphabetPattern,
+        AlphabetString,
+        Direction,
+        PackedAlphabetString
+    },
+    construction::{
+        ConstructionOptions,
+        ConstructionStats,
+        MemoryTracker
+    },
+    errors::Result,
+    parallel,
+    range::Range,
+    suffix_array::{
+        self,
+        TextPosition
+    }
+};
+
+// ======================================================================
+// == RIndex
+// ======================================================================
+
+/// Run-length compressed FM index of Gagie, Navarro and Prezza. All structures are stored per
+/// run of the BWT, so the size of the index grows with the amount of runs instead of the length
+/// of the text. Occurences are located with a toehold found during the backward search and the
+/// phi function.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RIndex<A: Alphabet, T: TextPosition = u32> {
+    /// Length of the text
+    n: usize,
+
+    /// Counts array
+    counts: Vec<usize>,
+
+    /// First row of every run
+    run_starts: Vec<T>,
+
+    /// Character of every run, the run of the sentinel has character 0
+    run_heads: PackedAlphabetString<A>,
+
+    /// Row of the sentinel
+    sentinel: usize,
+
+    /// For every character the runs of that character
+    character_runs: Vec<Vec<T>>,
+
+    /// For every character the total length of its runs before each of them
+    character_run_lengths: Vec<Vec<T>>,
+
+    /// Suffix array value at the last row of every run
+    run_end_samples: Vec<T>,
+
+    /// Sorted text positions just before the suffix array values at the run starts
+    phi_positions: Vec<T>,
+
+    /// The text position in the row before each of the phi positions
+    phi_values: Vec<T>
+}
+
+impl<A: Alphabet, T: TextPosition> RIndex<A, T> {
+    /// Construct a new r-index from a text
+    pub fn new(text: AlphabetString<A>) -> Self {
+        return Self::with_options(text, &ConstructionOptions::default())
+            .expect("construction without a memory budget can not fail")
+            .0;
+    }
+
+    /// Construct a new r-index from a text, reporting the peak memory usage
+    pub fn with_options(
+        text: AlphabetString<A>,
+        options: &ConstructionOptions
+    ) -> Result<(Self, ConstructionStats)> {
+        return parallel::install(options.threads, || Self::build(text, options.memory_budget));
+    }
+
+    fn build(
+        text: AlphabetString<A>,
+        memory_budget: Option<usize>
+    ) -> Result<(Self, ConstructionStats)> {
+        let memory = MemoryTracker::new(memory_budget);
+        memory.allocate(text.len())?;
+
+        let n = text.len();
+        let alphabet_length = text.alphabet.len();
+
+        // Create the suffix array
+        let sa = suffix_array::construct::<T, _>(&text[..], alphabet_length, &memory)?;
+
+        // The BWT character of a row, None for the sentinel
+        let bwt = |i: usize| -> Option<AlphabetIndex> {
+            if sa[i] == T::zero() {
+                return None;
+            }
+            return Some(text[sa[i].as_usize() - 1]);
+        };
+
+        // Split the BWT in runs, the sentinel always forms a run on its own
+        let mut run_starts = vec![];
+        let mut heads = vec![];
+        let mut run_end_samples = vec![];
+        let mut sentinel = 0;
+
+        for i in 0 ..= n {
+            let char_i = bwt(i);
+            if char_i.is_none() {
+                sentinel = i;
+            }
+
+            if i == 0 || char_i.is_none() || bwt(i - 1) != char_i {
+                if i > 0 {
+                    run_end_samples.push(sa[i - 1]);
+                }
+
+                run_starts.push(T::from_usize(i));
+                heads.push(char_i.unwrap_or(0));
+            }
+        }
+        run_end_samples.push(sa[n]);
+
+        let runs = run_starts.len();
+
+        let mut run_heads = PackedAlphabetString::with_alphabet(runs, text.alphabet.clone());
+        let mut character_runs = vec![vec![]; alphabet_length];
+        let mut character_run_lengths = vec![vec![T::zero()]; alphabet_length];
+
+        for j in 0 .. runs {
+            run_heads.set(j, heads[j]);
+
+            let start = run_starts[j].as_usize();
+            if start == sentinel {
+                continue;
+            }
+
+            let end = run_starts.get(j + 1).map_or(n + 1, |end| end.as_usize());
+            let lengths = &mut character_run_lengths[heads[j] as usize];
+
+            character_runs[heads[j] as usize].push(T::from_usize(j));
+            lengths.push(lengths[lengths.len() - 1] + T::from_usize(end - start));
+        }
+
+        // Phi is stored for the text positions just before the suffix array values at the run
+        // starts, every other text position is derived from the next stored one
+        let mut phi: HashMap<usize, usize> = run_starts
+            .iter()
+            .filter(|start| sa[start.as_usize()] != T::zero())
+            .map(|start| (sa[start.as_usize()].as_usize() - 1, 0))
+            .collect();
+
+        for i in 1 ..= n {
+            if let Some(value) = phi.get_mut(&sa[i].as_usize()) {
+                *value = sa[i - 1].as_usize();
+            }
+        }
+
+        let mut phi: Vec<(usize, usize)> = phi.into_iter().collect();
+        phi.sort_unstable();
+
+        let (phi_positions, phi_values) = phi
+            .into_iter()
+            .map(|(position, value)| (T::from_usize(position), T::from_usize(value)))
+            .unzip();
+
+        memory.free(sa.len() * mem::size_of::<T>());
+        drop(sa);
+
+        // Initialize the counts table
+        let mut counts = vec![0; alphabet_length];
+        for char_i in text.iter() {
+            counts[*char_i as usize] += 1;
+        }
+
+        let mut s1 = 1;
+        for count in counts.iter_mut() {
+            let s2 = *count;
+            *count = s1;
+            s1 += s2;
+        }
+
+        let index = RIndex {
+            n:                     n,
+            counts:                counts,
+            run_starts:            run_starts,
+            run_heads:             run_heads,
+            sentinel:              sentinel,
+            character_runs:        character_runs,
+            character_run_lengths: character_run_lengths,
+            run_end_samples:       run_end_samples,
+            phi_positions:         phi_positions,
+            phi_values:            phi_values
+        };
+        memory.allocate(index.size_in_bytes())?;
+
+        return Ok((index, memory.stats()));
+    }
+
+    /// Get the alphabet of the indexed text
+    pub fn alphabet(&self) -> &A {
+        return &self.run_heads.alphabet;
+    }
+
+    /// Get the amount of runs in the BWT
+    pub fn runs(&self) -> usize {
+        return self.run_starts.len();
+    }
+
+    /// Get the amount of bytes used by the index
+    pub fn size_in_bytes(&self) -> usize {
+        let runs: usize = self.character_runs.iter().map(|runs| runs.len()).sum();
+        let lengths: usize = self
+            .character_run_lengths
+            .iter()
+            .map(|lengths| lengths.len())
+            .sum();
+
+        let positions = self.run_starts.len()
+            + runs
+            + lengths
+            + self.run_end_samples.len()
+            + self.phi_positions.len()
+            + self.phi_values.len();
+
+        return self.counts.len() * mem::size_of::<usize>()
+            + self.run_heads.size_in_bytes()
+            + positions * mem::size_of::<T>();
+    }
+
+    /// Get the range over the suffix array that matches the empty string
+    pub fn full_range(&self) -> Range<usize> {
+        return Range::new(0, self.n + 1);
+    }
+
+    /// Get the run that contains a row
+    fn run(&self, i: usize) -> usize {
+        return self
+            .run_starts
+            .partition_point(|start| start.as_usize() <= i)
+            - 1;
+    }
+
+    /// Check whether a row of the BWT holds a character
+    fn holds(&self, char_i: usize, i: usize) -> bool {
+        return i != self.sentinel && self.run_heads.get(self.run(i)) as usize == char_i;
+    }
+
+    /// Get the amount of occurences of a character before a row
+    fn occ(&self, char_i: usize, i: usize) -> usize {
+        if i == 0 {
+            return 0;
+        }
+
+        let j = self.run(i - 1);
+
+        // Runs of the character before the run of the row
+        let k = self.character_runs[char_i].partition_point(|run| run.as_usize() < j);
+        let mut occ = self.character_run_lengths[char_i][k].as_usize();
+
+        if self.holds(char_i, i - 1) {
+            occ += i - self.run_starts[j].as_usize();
+        }
+
+        return occ;
+    }
+
+    /// Get the text position in the row before the row of a text position
+    fn phi(&self, position: T) -> T {
+        // The next stored position, phi decreases by one for every position before it
+        let k = self.phi_positions.partition_point(|p| *p < position);
+        return self.phi_values[k] - (self.phi_positions[k] - position);
+    }
+
+    /// Try to add a character to the left
+    pub fn add_char_left(
+        &self,
+        char_i: usize,
+        range: &Range<usize>,
+        new_range: &mut Range<usize>
+    ) -> bool {
+        new_range.start = self.counts[char_i] + self.occ(char_i, range.start);
+        new_range.end = self.counts[char_i] + self.occ(char_i, range.end);
+
+        return !new_range.empty();
+    }
+
+    /// Find the range of a pattern along with the text position at its last row
+    fn backward_search(&self, pattern: &mut AlphabetPattern<A>) -> Option<(Range<usize>, T)> {
+        pattern.set_direction(Direction::BACKWARD);
+
+        let mut range = self.full_range();
+        let mut toehold = self.run_end_samples[self.runs() - 1];
+
+        for i in 0 .. pattern.len() {
+            let char_i = pattern[i] as usize;
+            let last = range.end - 1;
+
+            if !self.add_char_left(char_i, &range.clone(), &mut range) {
+                return None;
+            }
+
+            // The last occurence of the character in the old range maps to the last row of
+            // the new range. If it is not in the last row, it ends a run of the character.
+            if self.holds(char_i, last) {
+                toehold = toehold - T::one();
+            } else {
+                let j = self.run(last);
+                let k = self.character_runs[char_i].partition_point(|run| run.as_usize() < j);
+
+                let run = self.character_runs[char_i][k - 1].as_usize();
+                toehold = self.run_end_samples[run] - T::one();
+            }
+        }
+
+        return Some((range, toehold));
+    }
+
+    /// Count the occurences of a pattern
+    pub fn count(&self, pattern: &mut AlphabetPattern<A>) -> usize {
+        let mut range = self.full_range();
+
+        pattern.set_direction(Direction::BACKWARD);
+
+        for i in 0 .. pattern.len() {
+            if !self.add_char_left(pattern[i] as usize, &range.clone(), &mut range) {
+                return 0;
+            }
+        }
+
+        return range.width();
+    }
+
+    /// Perform an exact match for a given pattern
+    pub fn exact_match(&self, pattern: &mut AlphabetPattern<A>) -> Vec<T> {
+        let (range, toehold) = match self.backward_search(pattern) {
+            Some(found) => found,
+            None => return vec![]
+        };
+
+        // Walk up from the last row of the range
+        let mut result = Vec::with_capacity(range.width());
+        let mut position = toehold;
+
+        result.push(position);
+        for _ in 1 .. range.width() {
+            position = self.phi(position);
+            result.push(position);
+        }
+
+        return result;
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::io::{
+        BufReader,
+        BufWriter
+    };
+
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        alphabet::{
+            AlphabetPattern,
+            AlphabetString,
+            DNAAlphabet
+        },
+        index::{
+            fm_index::FMIndex,
+            r_index::RIndex
+        },
+        io::Binary,
+        testing::{
+            random_text,
+            substitute
+        }
+    };
+
+    const INPUT: &str = "AACTAGGGCAATGTTCAACG";
+
+    /// Copies of a random text with a few mutations each
+    fn haplotypes(rng: &mut StdRng, length: usize, copies: usize) -> String {
+        let reference = random_text(rng, length);
+
+        let mut text = String::new();
+        for _ in 0 .. copies {
+            text.push_str(&substitute(rng, &reference, 3));
+        }
+
+        return text;
+    }
+
+    fn assert_same_matches(text: &str, patterns: &[&str]) {
+        let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(text), 1);
+        let r_index = RIndex::<DNAAlphabet>::new(AlphabetString::from(text));
+
+        for pattern in patterns {
+            let mut expected = fm_index.exact_match(&mut AlphabetPattern::from(*pattern));
+            let mut result = r_index.exact_match(&mut AlphabetPattern::from(*pattern));
+            expected.sort();
+            result.sort();
+
+            assert_eq!(result, expected);
+            assert_eq!(r_index.count(&mut AlphabetPattern::from(*pattern)), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let r_index = RIndex::<DNAAlphabet>::new(AlphabetString::from(INPUT));
+
+        let patterns = vec!["A", "AC", "AACT", "AACG", "CCC"];
+
+        let results: Vec<Vec<u32>> =
+            vec![vec![0, 1, 4, 9, 10, 16, 17], vec![1, 17], vec![0], vec![16], vec![]];
+
+        for i in 0 .. patterns.len() {
+            let mut result = r_index.exact_match(&mut AlphabetPattern::from(patterns[i]));
+            result.sort();
+
+            assert_eq!(result, results[i]);
+        }
+    }
+
+    #[test]
+    fn test_exact_match_random() {
+        let mut rng = StdRng::seed_from_u64(17);
+
+        let text = random_text(&mut rng, 2_000);
+
+        let patterns: Vec<&str> = (0 .. 50)
+            .map(|_| {
+                let start = rng.gen_range(0 .. text.len() - 8);
+                &text[start .. start + rng.gen_range(1 ..= 8)]
+            })
+            .collect();
+
+        assert_same_matches(&text, &patterns);
+        assert_same_matches(&text, &["", "AAAAAAAAAAAAAAAA"]);
+    }
+
+    #[test]
+    fn test_exact_match_repetitive() {
+        let mut rng = StdRng::seed_from_u64(17);
+
+        let text = haplotypes(&mut rng, 1_000, 20);
+
+        let patterns: Vec<&str> = (0 .. 50)
+            .map(|_| {
+                let start = rng.gen_range(0 .. text.len() - 20);
+                &text[start .. start + rng.gen_range(1 ..= 20)]
+            })
+            .collect();
+
+        assert_same_matches(&text, &patterns);
+    }
+
+    #[test]
+    fn test_size_in_bytes() {
+        let mut rng = StdRng::seed_from_u64(17);
+
+        let text = haplotypes(&mut rng, 5_000, 100);
+        let r_index = RIndex::<DNAAlphabet>::new(AlphabetString::from(text.as_str()));
+
+        // The amount of runs is close to the length of one haplotype, not of the whole text
+        assert!(r_index.runs() < 20_000);
+        assert!(r_index.size_in_bytes() < text.len());
+    }
+
+    #[test]
+    fn test_u64() {
+        let r_index = RIndex::<DNAAlphabet, u64>::new(AlphabetString::from(INPUT));
+
+        let mut result = r_index.exact_match(&mut AlphabetPattern::from("AC"));
+        result.sort();
+
+        assert_eq!(result, vec![1, 17]);
+    }
+
+    #[test]
+    fn test_binary() {
+        let r_index = RIndex::<DNAAlphabet>::new(AlphabetString::from(INPUT));
+
+        let mut buffer = vec![];
+        r_index.to_bin(BufWriter::new(&mut buffer)).unwrap();
+
+        let r_index = RIndex::<DNAAlphabet>::from_bin(BufReader::new(&buffer[..])).unwrap();
+
+        let mut result = r_index.exact_match(&mut AlphabetPattern::from("A"));
+        result.sort();
+
+        assert_eq!(result, vec![0, 1, 4, 9, 10, 16, 17]);
+    }
+}
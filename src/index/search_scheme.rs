@@ -0,0 +1,417 @@
+use crate::{
+    errors::{
+        ErrorKind,
+        Result
+    },
+    range::Range
+};
+
+// ======================================================================
+// == Distance
+// ======================================================================
+
+/// Distance between a pattern and its occurences
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Distance {
+    /// Only substitutions are allowed
+    HAMMING,
+
+    /// Substitutions, insertions and deletions are allowed
+    EDIT
+}
+
+// ======================================================================
+// == Search
+// ======================================================================
+
+/// Search of a search scheme: the order in which the parts of a pattern are matched, with bounds
+/// on the amount of errors after every part
+#[derive(Clone, PartialEq, Debug)]
+pub struct Search {
+    /// The order in which the parts are matched, every part is adjacent to the parts before it
+    order: Vec<usize>,
+
+    /// Minimum amount of errors after every part
+    lower: Vec<usize>,
+
+    /// Maximum amount of errors after every part
+    upper: Vec<usize>
+}
+
+impl Search {
+    pub fn new(order: Vec<usize>, lower: Vec<usize>, upper: Vec<usize>) -> Result<Self> {
+        let parts = order.len();
+
+        if parts == 0 || lower.len() != parts || upper.len() != parts {
+            bail!(ErrorKind::InvalidSearchScheme(format!(
+                "the order and both bounds need the same amount of parts, got {}, {} and {}",
+                parts,
+                lower.len(),
+                upper.len()
+            )));
+        }
+
+        // The matched parts form an interval that grows by one part at a time
+        let (mut start, mut end) = (order[0], order[0] + 1);
+        for part in order[1 ..].iter() {
+            if *part + 1 == start {
+                start -= 1;
+            } else if *part == end {
+                end += 1;
+            } else {
+                bail!(ErrorKind::InvalidSearchScheme(format!(
+                    "part {} is not adjacent to the parts before it in {:?}",
+                    part, order
+                )));
+            }
+        }
+
+        if start != 0 || end != parts {
+            bail!(ErrorKind::InvalidSearchScheme(format!("{:?} is not a permutation", order)));
+        }
+
+        for i in 0 .. parts {
+            if lower[i] > upper[i] {
+                bail!(ErrorKind::InvalidSearchScheme(format!(
+                    "lower bound {} exceeds upper bound {} of part {}",
+                    lower[i], upper[i], i
+                )));
+            }
+
+            if i > 0 && (lower[i] < lower[i - 1] || upper[i] < upper[i - 1]) {
+                bail!(ErrorKind::InvalidSearchScheme(format!(
+                    "the bounds of part {} are smaller than those of the part before it",
+                    i
+                )));
+            }
+        }
+
+        return Ok(Self {
+            order: order,
+            lower: lower,
+            upper: upper
+        });
+    }
+
+    /// Get the order in which the parts are matched
+    pub fn order(&self) -> &[usize] {
+        return &self.order;
+    }
+
+    /// Get the minimum amount of errors after every part
+    pub fn lower(&self) -> &[usize] {
+        return &self.lower;
+    }
+
+    /// Get the maximum amount of errors after every part
+    pub fn upper(&self) -> &[usize] {
+        return &self.upper;
+    }
+
+    /// Get the amount of parts
+    pub fn parts(&self) -> usize {
+        return self.order.len();
+    }
+
+    /// Check whether the `i`th part in the order is added to the right of the parts before it
+    pub fn extends_right(&self, i: usize) -> bool {
+        if i == 0 {
+            // The first part is matched in the direction of the second one
+            return self.order.len() == 1 || self.order[1] > self.order[0];
+        }
+
+        return self.order[i] > self.order[0];
+    }
+
+    /// Check whether the search finds an occurence with the given amount of errors in every part
+    pub fn covers(&self, errors: &[usize]) -> bool {
+        let mut total = 0;
+
+        for i in 0 .. self.parts() {
+            total += errors[self.order[i]];
+
+            if total < self.lower[i] || total > self.upper[i] {
+                return false;
+            }
+        }
+
+        return true;
+    }
+}
+
+// ======================================================================
+// == SearchScheme
+// ======================================================================
+
+/// Set of searches that together find every occurence of a pattern with at most a given amount
+/// of errors, as introduced by Kucherov et al.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchScheme {
+    /// The searches of the scheme
+    searches: Vec<Search>
+}
+
+impl SearchScheme {
+    pub fn new(searches: Vec<Search>) -> Result<Self> {
+        if searches.is_empty() {
+            bail!(ErrorKind::InvalidSearchScheme("a scheme needs at least one search".to_string()));
+        }
+
+        if searches
+            .iter()
+            .any(|search| search.parts() != searches[0].parts())
+        {
+            bail!(ErrorKind::InvalidSearchScheme(
+                "all searches need the same amount of parts".to_string()
+            ));
+        }
+
+        return Ok(Self {
+            searches: searches
+        });
+    }
+
+    /// Get the optimum search scheme of Kianfar et al. for at most `k` errors, for `k` up to 3
+    pub fn optimal(k: usize) -> Option<Self> {
+        let searches: &[(&[usize], &[usize], &[usize])] = match k {
+            0 => &[(&[0], &[0], &[0])],
+            1 => &[(&[0, 1], &[0, 0], &[0, 1]), (&[1, 0], &[0, 1], &[0, 1])],
+            2 => &[
+                (&[0, 1, 2, 3], &[0, 0, 1, 1], &[0, 0, 2, 2]),
+                (&[2, 1, 0, 3], &[0, 0, 0, 0], &[0, 1, 1, 2]),
+                (&[3, 2, 1, 0], &[0, 0, 0, 2], &[0, 1, 2, 2])
+            ],
+            3 => &[
+                (&[0, 1, 2, 3, 4], &[0, 0, 0, 0, 3], &[0, 1, 2, 3, 3]),
+                (&[1, 2, 3, 4, 0], &[0, 0, 0, 2, 2], &[0, 1, 2, 2, 3]),
+                (&[2, 3, 4, 1, 0], &[0, 0, 1, 1, 1], &[0, 1, 1, 3, 3]),
+                (&[4, 3, 2, 1, 0], &[0, 0, 0, 0, 0], &[0, 0, 3, 3, 3])
+            ],
+            _ => return None
+        };
+
+        return Some(Self::from_table(searches));
+    }
+
+    /// Get a built-in search scheme for at most `k` errors, for `k` up to 4. Up to 3 errors this
+    /// is the optimum scheme.
+    ///
+    /// Known gap: the scheme for 4 errors is not the optimum scheme of Kianfar et al. but a greedy
+    /// selection of searches. It finds every occurence, but may visit more nodes than needed.
+    pub fn builtin(k: usize) -> Option<Self> {
+        // TODO: replace by the optimum scheme of Kianfar et al. for 4 errors and move it to
+        // `optimal`
+        if k == 4 {
+            return Some(Self::from_table(&[
+                (&[0, 1, 2, 3, 4, 5], &[0, 0, 0, 0, 0, 0], &[0, 3, 4, 4, 4, 4]),
+                (&[2, 3, 4, 5, 1, 0], &[0, 0, 0, 0, 0, 1], &[0, 3, 3, 3, 4, 4]),
+                (&[3, 4, 5, 2, 1, 0], &[0, 0, 0, 1, 1, 2], &[0, 2, 2, 3, 3, 4]),
+                (&[1, 2, 3, 4, 5, 0], &[0, 1, 2, 2, 2, 3], &[0, 2, 3, 3, 3, 4]),
+                (&[5, 4, 3, 2, 1, 0], &[0, 0, 1, 2, 3, 4], &[0, 0, 1, 2, 3, 4])
+            ]));
+        }
+
+        return Self::optimal(k);
+    }
+
+    /// Get a scheme with a single search over the whole pattern, allowing at most `k` errors
+    pub fn single(k: usize) -> Self {
+        return Self::from_table(&[(&[0], &[0], &[k])]);
+    }
+
+    /// Create a scheme from a table of orders, lower bounds and upper bounds
+    fn from_table(searches: &[(&[usize], &[usize], &[usize])]) -> Self {
+        let searches = searches
+            .iter()
+            .map(|(order, lower, upper)| {
+                Search::new(order.to_vec(), lower.to_vec(), upper.to_vec())
+                    .expect("the built-in searches are valid")
+            })
+            .collect();
+
+        return Self {
+            searches: searches
+        };
+    }
+
+    /// Get the searches of the scheme
+    pub fn searches(&self) -> &[Search] {
+        return &self.searches;
+    }
+
+    /// Get the amount of parts a pattern is split in
+    pub fn parts(&self) -> usize {
+        return self.searches[0].parts();
+    }
+
+    /// Get the maximum amount of errors any search allows
+    pub fn max_errors(&self) -> usize {
+        return self
+            .searches
+            .iter()
+            .map(|search| search.upper[search.parts() - 1])
+            .max()
+            .unwrap_or(0);
+    }
+
+    /// Split a pattern in parts of (almost) equal length, the first parts are the longest
+    pub fn partition(&self, pattern_length: usize) -> Result<Vec<Range<usize>>> {
+        let parts = self.parts();
+
+        if pattern_length < parts {
+            bail!(ErrorKind::InvalidSearchScheme(format!(
+                "a pattern of length {} can not be split in {} parts",
+                pattern_length, parts
+            )));
+        }
+
+        let mut ranges = Vec::with_capacity(parts);
+
+        let mut start = 0;
+        for i in 0 .. parts {
+            let length = pattern_length / parts + (i < pattern_length % parts) as usize;
+            ranges.push(Range::new(start, start + length));
+            start += length;
+        }
+
+        return Ok(ranges);
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        errors::ErrorKind,
+        index::search_scheme::{
+            Search,
+            SearchScheme
+        },
+        range::Range
+    };
+
+    /// Get every distribution of at most `k` errors over `parts` parts
+    fn distributions(parts: usize, k: usize) -> Vec<Vec<usize>> {
+        let mut result = vec![vec![]];
+
+        for _ in 0 .. parts {
+            result = result
+                .into_iter()
+                .flat_map(|errors: Vec<usize>| {
+                    let total: usize = errors.iter().sum();
+                    (0 ..= k - total).map(move |e| [errors.clone(), vec![e]].concat())
+                })
+                .collect();
+        }
+
+        return result;
+    }
+
+    #[test]
+    fn test_search_new() {
+        let search = Search::new(vec![2, 1, 0, 3], vec![0, 0, 0, 0], vec![0, 1, 1, 2]).unwrap();
+
+        assert_eq!(search.parts(), 4);
+        assert_eq!(search.extends_right(0), false);
+        assert_eq!(search.extends_right(1), false);
+        assert_eq!(search.extends_right(2), false);
+        assert_eq!(search.extends_right(3), true);
+
+        assert!(search.covers(&[1, 0, 0, 1]));
+        assert!(!search.covers(&[0, 2, 0, 0]));
+
+        let invalid = [
+            (vec![0, 2, 1], vec![0, 0, 0], vec![0, 1, 1]),
+            (vec![0, 1, 1], vec![0, 0, 0], vec![0, 1, 1]),
+            (vec![0, 1], vec![0, 0, 0], vec![0, 1, 1]),
+            (vec![0, 1, 2], vec![0, 2, 0], vec![0, 2, 2]),
+            (vec![0, 1, 2], vec![0, 0, 2], vec![0, 1, 1]),
+            (vec![], vec![], vec![])
+        ];
+
+        for (order, lower, upper) in invalid.iter() {
+            match Search::new(order.clone(), lower.clone(), upper.clone()) {
+                Err(e) => match e.kind() {
+                    ErrorKind::InvalidSearchScheme(_) => (),
+                    _ => panic!("unexpected error: {}", e)
+                },
+                Ok(_) => panic!("{:?} {:?} {:?} was not rejected", order, lower, upper)
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_scheme_new() {
+        let search_2 = Search::new(vec![0, 1], vec![0, 0], vec![0, 1]).unwrap();
+        let search_3 = Search::new(vec![0, 1, 2], vec![0, 0, 0], vec![0, 1, 1]).unwrap();
+
+        assert!(SearchScheme::new(vec![]).is_err());
+        assert!(SearchScheme::new(vec![search_2.clone(), search_3]).is_err());
+
+        let scheme = SearchScheme::new(vec![search_2]).unwrap();
+        assert_eq!(scheme.parts(), 2);
+        assert_eq!(scheme.max_errors(), 1);
+    }
+
+    /// Check that every distribution of at most `k` errors is covered by a search
+    fn assert_covers(scheme: &SearchScheme, k: usize) {
+        assert_eq!(scheme.max_errors(), k);
+
+        for errors in distributions(scheme.parts(), k) {
+            assert!(
+                scheme
+                    .searches()
+                    .iter()
+                    .any(|search| search.covers(&errors)),
+                "{:?} is not covered for k = {}",
+                errors,
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimal() {
+        for k in 0 ..= 3 {
+            assert_covers(&SearchScheme::optimal(k).unwrap(), k);
+        }
+
+        // 4 errors are only covered by the greedy built-in scheme, which is not optimal
+        assert_covers(&SearchScheme::builtin(4).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_builtin() {
+        for k in 0 ..= 3 {
+            assert_eq!(SearchScheme::builtin(k), SearchScheme::optimal(k));
+        }
+
+        assert_eq!(SearchScheme::builtin(5), None);
+    }
+
+    #[test]
+    fn test_single() {
+        let scheme = SearchScheme::single(2);
+
+        assert_eq!(scheme.parts(), 1);
+        assert_covers(&scheme, 2);
+    }
+
+    #[test]
+    fn test_partition() {
+        let scheme = SearchScheme::optimal(2).unwrap();
+
+        assert_eq!(
+            scheme.partition(10).unwrap(),
+            vec![Range::new(0, 3), Range::new(3, 6), Range::new(6, 8), Range::new(8, 10)]
+        );
+        assert_eq!(
+            scheme.partition(4).unwrap(),
+            vec![Range::new(0, 1), Range::new(1, 2), Range::new(2, 3), Range::new(3, 4)]
+        );
+        assert!(scheme.partition(3).is_err());
+    }
+}
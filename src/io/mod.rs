@@ -12,10 +12,17 @@ use serde::{
 
 use crate::{
     alphabet::Alphabet,
+    bitvector::{
+        Occurences,
+        RankSelect
+    },
     index::{
         bidirectional_fm_index::BidirectionalFMIndex,
-        fm_index::FMIndex
-    }
+        collection::FMIndexCollection,
+        fm_index::FMIndex,
+        r_index::RIndex
+    },
+    suffix_array::TextPosition
 };
 
 pub trait Binary {
@@ -36,9 +43,39 @@ pub trait Binary {
     }
 }
 
-impl<A: Alphabet> Binary for FMIndex<A> {}
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Binary for FMIndex<A, T, O, B> {}
+
+impl<A: Alphabet> FMIndex<A> {
+    /// Read an index with the default position type and backends
+    pub fn from_bin<R: Read>(reader: BufReader<R>) -> Result<Self>
+    where
+        for<'de> Self: Deserialize<'de>
+    {
+        return <Self as Binary>::from_bin(reader);
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Binary
+    for BidirectionalFMIndex<A, T, O, B>
+{
+}
+
+impl<A: Alphabet> BidirectionalFMIndex<A> {
+    /// Read an index with the default position type and backends
+    pub fn from_bin<R: Read>(reader: BufReader<R>) -> Result<Self>
+    where
+        for<'de> Self: Deserialize<'de>
+    {
+        return <Self as Binary>::from_bin(reader);
+    }
+}
+
+impl<A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Binary
+    for FMIndexCollection<A, T, O, B>
+{
+}
 
-impl<A: Alphabet> Binary for BidirectionalFMIndex<A> {}
+impl<A: Alphabet, T: TextPosition> Binary for RIndex<A, T> {}
 
 error_chain! {
     foreign_links {
@@ -63,7 +100,15 @@ mod tests {
         Serialize
     };
 
-    use crate::io::Binary;
+    use crate::{
+        alphabet::{
+            AlphabetPattern,
+            AlphabetString,
+            CustomAlphabet
+        },
+        index::fm_index::FMIndex,
+        io::Binary
+    };
 
     #[derive(Serialize, Deserialize)]
     struct TestStruct {
@@ -121,4 +166,36 @@ mod tests {
 
         fs::remove_file("./test_from_bin");
     }
+
+    #[test]
+    fn test_custom_alphabet_round_trip() {
+        let alphabet = CustomAlphabet::from_specification("ACGT U=T", true).unwrap();
+
+        let fm_index = FMIndex::<CustomAlphabet>::new(
+            AlphabetString::from_alphabet("aacuagggcaaugttcaacg", alphabet),
+            3
+        );
+
+        let f = File::create("./test_custom_alphabet").expect("Unable to create file");
+        let f = BufWriter::new(f);
+
+        fm_index.to_bin(f).expect("Unable to write the index");
+
+        let f = File::open("./test_custom_alphabet").expect("Unable to open file");
+        let f = BufReader::new(f);
+
+        let fm_index_new =
+            FMIndex::<CustomAlphabet>::from_bin(f).expect("Unable to read the index");
+
+        fs::remove_file("./test_custom_alphabet").expect("Unable to remove file");
+
+        // The alphabet is restored along with the index
+        let mut pattern = AlphabetPattern::from(AlphabetString::from_alphabet(
+            "caau",
+            fm_index_new.alphabet().clone()
+        ));
+
+        assert_eq!(fm_index_new.alphabet(), fm_index.alphabet());
+        assert_eq!(fm_index_new.exact_match(&mut pattern), vec![8]);
+    }
 }
@@ -3,10 +3,17 @@ extern crate error_chain;
 
 pub mod alphabet;
 pub mod bitvector;
+pub mod construction;
 pub mod errors;
 pub mod index;
 pub mod io;
 pub mod matrix;
+pub mod myers;
+pub mod parallel;
 pub mod range;
+pub mod rrr;
 pub mod suffix_array;
+#[cfg(test)]
+mod testing;
 pub mod tree;
+pub mod wavelet_tree;
@@ -16,6 +16,167 @@ use crate::alphabet::{
     AlphabetPattern
 };
 
+/// Operation of a CIGAR string, aligning a pattern (the query) to the text (the reference)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CigarOperation {
+    /// A pattern character equal to its text character
+    MATCH,
+
+    /// A pattern character different from its text character
+    MISMATCH,
+
+    /// A pattern character missing from the text
+    INSERTION,
+
+    /// A text character missing from the pattern
+    DELETION
+}
+
+impl CigarOperation {
+    /// Get the SAM symbol of the operation
+    pub fn symbol(&self) -> char {
+        return match self {
+            CigarOperation::MATCH => '=',
+            CigarOperation::MISMATCH => 'X',
+            CigarOperation::INSERTION => 'I',
+            CigarOperation::DELETION => 'D'
+        };
+    }
+}
+
+/// Run length encoded alignment of a pattern to the text
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cigar {
+    /// The operations with their run lengths
+    operations: Vec<(CigarOperation, usize)>
+}
+
+impl Cigar {
+    pub fn new() -> Self {
+        Self {
+            operations: vec![]
+        }
+    }
+
+    /// Add an operation at the end of the alignment
+    pub fn push(&mut self, operation: CigarOperation) {
+        match self.operations.last_mut() {
+            Some((last, count)) if *last == operation => *count += 1,
+            _ => self.operations.push((operation, 1))
+        }
+    }
+
+    /// Align a pattern to a text of the same length with substitutions only, if there are at
+    /// most `b` mismatches
+    pub fn hamming<A: Alphabet>(
+        pattern: &AlphabetPattern<A>,
+        text: &[AlphabetIndex],
+        b: usize
+    ) -> Option<Self> {
+        if text.len() != pattern.len() {
+            return None;
+        }
+
+        let mut cigar = Self::new();
+        for (i, c) in text.iter().enumerate() {
+            cigar.push(if pattern[i] == *c {
+                CigarOperation::MATCH
+            } else {
+                CigarOperation::MISMATCH
+            });
+        }
+
+        if cigar.distance() > b {
+            return None;
+        }
+
+        return Some(cigar);
+    }
+
+    /// Get the operations with their run lengths
+    pub fn operations(&self) -> &[(CigarOperation, usize)] {
+        return &self.operations;
+    }
+
+    /// Get the amount of pattern characters in the alignment
+    pub fn pattern_length(&self) -> usize {
+        return self.count(&[
+            CigarOperation::MATCH,
+            CigarOperation::MISMATCH,
+            CigarOperation::INSERTION
+        ]);
+    }
+
+    /// Get the amount of text characters in the alignment
+    pub fn text_length(&self) -> usize {
+        return self.count(&[
+            CigarOperation::MATCH,
+            CigarOperation::MISMATCH,
+            CigarOperation::DELETION
+        ]);
+    }
+
+    /// Get the amount of edits in the alignment
+    pub fn distance(&self) -> usize {
+        return self.count(&[
+            CigarOperation::MISMATCH,
+            CigarOperation::INSERTION,
+            CigarOperation::DELETION
+        ]);
+    }
+
+    fn count(&self, operations: &[CigarOperation]) -> usize {
+        return self
+            .operations
+            .iter()
+            .filter(|(operation, _)| operations.contains(operation))
+            .map(|(_, count)| count)
+            .sum();
+    }
+}
+
+impl Default for Cigar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats the alignment as a SAM CIGAR string, an empty alignment is `*`
+impl fmt::Display for Cigar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.operations.is_empty() {
+            return write!(f, "*");
+        }
+
+        for (operation, count) in self.operations.iter() {
+            write!(f, "{}{}", count, operation.symbol())?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Edit distances between a pattern and the text characters along a search tree path, one row
+/// per text character. Cells within `b` of the diagonal that are at most `b` have to be exact.
+pub trait EditMatrix {
+    /// Create a matrix for a pattern allowing at most `b` edits
+    fn with_pattern<A: Alphabet>(pattern: &AlphabetPattern<A>, b: usize) -> Self;
+
+    /// Fill `row` for text character `c`, returning the minimum of the row within the band
+    fn update_row<A: Alphabet>(
+        &mut self,
+        pattern: &AlphabetPattern<A>,
+        row: usize,
+        c: AlphabetIndex
+    ) -> usize;
+
+    /// Check whether the band of `row` reaches the end of the pattern
+    fn in_final_column(&self, row: usize) -> bool;
+
+    /// Get the edit distance between the pattern and the first `row` text characters
+    fn final_column(&self, row: usize) -> usize;
+}
+
 pub struct BandedMatrix {
     /// Number of rows
     n: usize,
@@ -66,13 +227,13 @@ impl BandedMatrix {
             matrix[index(i, 0)] = i;
         }
 
-        // Set max to the right of first b rows
-        for i in 1 ..= b {
+        // Set max to the right of first b rows, including the top row
+        for i in 0 ..= b {
             matrix[index(i, i + b + 1)] = b + 1;
         }
 
         // Set max to left and right for other rows
-        for i in b + 1 .. m - b - 1 {
+        for i in b + 1 .. m.saturating_sub(b + 1) {
             matrix[index(i, i + b + 1)] = b + 1;
             matrix[index(i, i - b - 1)] = b + 1;
         }
@@ -119,6 +280,76 @@ impl BandedMatrix {
         return minimum;
     }
 
+    /// Align a pattern to a text with at most `b` edits, if possible
+    pub fn align<A: Alphabet>(
+        pattern: &AlphabetPattern<A>,
+        text: &[AlphabetIndex],
+        b: usize
+    ) -> Option<Cigar> {
+        if text.len() > pattern.len() + b {
+            return None;
+        }
+
+        let mut matrix = Self::new(pattern.len(), b);
+
+        for (i, c) in text.iter().enumerate() {
+            matrix.update_row(pattern, i + 1, *c);
+        }
+
+        if !matrix.in_final_column(text.len()) || matrix.final_column(text.len()) > b {
+            return None;
+        }
+
+        return Some(matrix.traceback(pattern, text));
+    }
+
+    /// Find the alignment ending in the final column of the last row, after filling the rows
+    /// with the characters of `text`. Matches and mismatches are preferred over indels.
+    pub fn traceback<A: Alphabet>(
+        &self,
+        pattern: &AlphabetPattern<A>,
+        text: &[AlphabetIndex]
+    ) -> Cigar {
+        let mut operations = vec![];
+
+        let mut row = text.len();
+        let mut column = self.m - 1;
+
+        while row > 0 || column > 0 {
+            let value = self[[row, column]];
+
+            if row > 0 && column > 0 {
+                let mismatch = text[row - 1] != pattern[column - 1];
+
+                if self[[row - 1, column - 1]] + mismatch as usize == value {
+                    operations.push(match mismatch {
+                        true => CigarOperation::MISMATCH,
+                        false => CigarOperation::MATCH
+                    });
+
+                    row -= 1;
+                    column -= 1;
+                    continue;
+                }
+            }
+
+            if column > 0 && (row == 0 || self[[row, column - 1]] + 1 == value) {
+                operations.push(CigarOperation::INSERTION);
+                column -= 1;
+            } else {
+                operations.push(CigarOperation::DELETION);
+                row -= 1;
+            }
+        }
+
+        let mut cigar = Cigar::new();
+        for operation in operations.into_iter().rev() {
+            cigar.push(operation);
+        }
+
+        return cigar;
+    }
+
     pub fn in_final_column(&self, row: usize) -> bool {
         return self.last_column(row) == self.m - 1;
     }
@@ -128,6 +359,29 @@ impl BandedMatrix {
     }
 }
 
+impl EditMatrix for BandedMatrix {
+    fn with_pattern<A: Alphabet>(pattern: &AlphabetPattern<A>, b: usize) -> Self {
+        return Self::new(pattern.len(), b);
+    }
+
+    fn update_row<A: Alphabet>(
+        &mut self,
+        pattern: &AlphabetPattern<A>,
+        row: usize,
+        c: AlphabetIndex
+    ) -> usize {
+        return BandedMatrix::update_row(self, pattern, row, c);
+    }
+
+    fn in_final_column(&self, row: usize) -> bool {
+        return BandedMatrix::in_final_column(self, row);
+    }
+
+    fn final_column(&self, row: usize) -> usize {
+        return BandedMatrix::final_column(self, row);
+    }
+}
+
 impl Index<[usize; 2]> for BandedMatrix {
     type Output = usize;
 
@@ -180,9 +434,14 @@ mod tests {
     use crate::{
         alphabet::{
             AlphabetPattern,
+            AlphabetString,
             DNAAlphabet
         },
-        matrix::BandedMatrix
+        matrix::{
+            BandedMatrix,
+            Cigar,
+            CigarOperation
+        }
     };
 
     #[test]
@@ -190,7 +449,7 @@ mod tests {
         let banded_matrix = BandedMatrix::new(6, 1);
 
         let result = vec![
-            0, 0, 1, 0, 0, 1, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 0,
+            0, 0, 1, 2, 0, 1, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 2, 2, 0, 0, 0, 0,
             2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0,
         ];
 
@@ -286,4 +545,78 @@ mod tests {
             assert_eq!(banded_matrix.final_column(i), 1);
         }
     }
+
+    #[test]
+    fn test_cigar() {
+        let mut cigar = Cigar::new();
+        assert_eq!(cigar.to_string(), "*");
+
+        for operation in [
+            CigarOperation::MATCH,
+            CigarOperation::MATCH,
+            CigarOperation::MISMATCH,
+            CigarOperation::INSERTION,
+            CigarOperation::MATCH,
+            CigarOperation::DELETION,
+            CigarOperation::DELETION
+        ] {
+            cigar.push(operation);
+        }
+
+        assert_eq!(cigar.to_string(), "2=1X1I1=2D");
+        assert_eq!(cigar.pattern_length(), 5);
+        assert_eq!(cigar.text_length(), 6);
+        assert_eq!(cigar.distance(), 4);
+    }
+
+    #[test]
+    fn test_align() {
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("ACAAGT");
+
+        let alignments = [
+            ("ACAAGT", 0, Some("6=")),
+            ("ACATGT", 1, Some("3=1X2=")),
+            ("ACAGT", 1, Some("2=1I3=")),
+            ("ACAAAGT", 1, Some("2=1D4=")),
+            ("CAAGTT", 2, Some("1I4=1D1=")),
+            ("ACATGT", 0, None),
+            ("ACAAGTTT", 1, None),
+            ("", 6, Some("6I"))
+        ];
+
+        for (text, b, expected) in alignments.iter() {
+            let text = AlphabetString::<DNAAlphabet>::from(*text);
+
+            assert_eq!(
+                BandedMatrix::align(&pattern, &text, *b).map(|cigar| cigar.to_string()),
+                expected.map(|cigar| cigar.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_hamming() {
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("ACGT");
+
+        let alignments = [
+            ("ACGT", 0, Some("4=")),
+            ("ACTT", 1, Some("2=1X1=")),
+            ("CGTA", 4, Some("4X")),
+            ("CGTA", 3, None),
+            ("ACG", 1, None)
+        ];
+
+        for (text, b, expected) in alignments.iter() {
+            let text = AlphabetString::<DNAAlphabet>::from(*text);
+
+            assert_eq!(
+                Cigar::hamming(&pattern, &text, *b).map(|cigar| cigar.to_string()),
+                expected.map(|cigar| cigar.to_string())
+            );
+        }
+
+        // The edit distance allows a cheaper alignment with indels
+        let text = AlphabetString::<DNAAlphabet>::from("CGTA");
+        assert_eq!(BandedMatrix::align(&pattern, &text, 2).unwrap().to_string(), "1I3=1D");
+    }
 }
@@ -0,0 +1,318 @@
+use std::cmp::{
+    max,
+    min
+};
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetIndex,
+        AlphabetPattern
+    },
+    matrix::EditMatrix
+};
+
+const WORD_SIZE: usize = 64;
+
+const HIGH_BIT: u64 = 1 << (WORD_SIZE - 1);
+
+/// Bit-parallel edit distance matrix of Myers, in the block based formulation of Hyyrö. Every
+/// row stores the vertical differences between consecutive pattern positions as bit vectors,
+/// so a row is filled in O(m / 64) instead of O(b).
+pub struct MyersMatrix {
+    /// Length of the pattern
+    pattern_size: usize,
+
+    /// Maximum amount of edits
+    b: usize,
+
+    /// Amount of words per row
+    words: usize,
+
+    /// Pattern positions of every character
+    peq: Vec<Vec<u64>>,
+
+    /// Positions where the distance increases by one from the previous pattern position
+    vp: Vec<u64>,
+
+    /// Positions where the distance decreases by one from the previous pattern position
+    vn: Vec<u64>
+}
+
+impl MyersMatrix {
+    pub fn new<A: Alphabet>(pattern: &AlphabetPattern<A>, b: usize) -> Self {
+        let pattern_size = pattern.len();
+        let words = pattern_size.div_ceil(WORD_SIZE);
+        let rows = pattern_size + b + 1;
+
+        let mut peq: Vec<Vec<u64>> = vec![];
+        for i in 0 .. pattern_size {
+            let c = pattern[i] as usize;
+
+            if c >= peq.len() {
+                peq.resize(c + 1, vec![0; words]);
+            }
+
+            peq[c][i / WORD_SIZE] |= 1 << (i % WORD_SIZE);
+        }
+
+        // The first row increases by one at every pattern position
+        let mut vp = vec![0; rows * words];
+        for word in vp[.. words].iter_mut() {
+            *word = u64::MAX;
+        }
+
+        Self {
+            pattern_size: pattern_size,
+            b:            b,
+            words:        words,
+            peq:          peq,
+            vp:           vp,
+            vn:           vec![0; rows * words]
+        }
+    }
+
+    /// Compute a block of a row from the block above it and the horizontal difference `hin`
+    /// entering the block, returning the horizontal difference leaving it
+    fn step(vp: &mut u64, vn: &mut u64, eq: u64, hin: i8) -> i8 {
+        let hin_negative = (hin < 0) as u64;
+        let xv = eq | *vn;
+        let eq = eq | hin_negative;
+        let xh = ((eq & *vp).wrapping_add(*vp) ^ *vp) | eq;
+
+        let mut hp = *vn | !(xh | *vp);
+        let mut hn = *vp & xh;
+
+        let hout = match (hp & HIGH_BIT != 0, hn & HIGH_BIT != 0) {
+            (true, _) => 1,
+            (_, true) => -1,
+            _ => 0
+        };
+
+        hp = (hp << 1) | (hin > 0) as u64;
+        hn = (hn << 1) | hin_negative;
+
+        *vp = hn | !(xv | hp);
+        *vn = hp & xv;
+
+        return hout;
+    }
+
+    /// Get the edit distance between the first `column` pattern characters and the first `row`
+    /// text characters
+    fn value(&self, row: usize, column: usize) -> usize {
+        let offset = row * self.words;
+        let mut value = row as isize;
+
+        for word in 0 .. column / WORD_SIZE {
+            value += self.vp[offset + word].count_ones() as isize;
+            value -= self.vn[offset + word].count_ones() as isize;
+        }
+
+        if !column.is_multiple_of(WORD_SIZE) {
+            let mask = (1 << (column % WORD_SIZE)) - 1;
+            value += (self.vp[offset + column / WORD_SIZE] & mask).count_ones() as isize;
+            value -= (self.vn[offset + column / WORD_SIZE] & mask).count_ones() as isize;
+        }
+
+        return value as usize;
+    }
+
+    /// Get the minimum of `row` from column `first` up to and including column `last`. It is
+    /// found at `first` or right after a decrease.
+    fn minimum(&self, row: usize, first: usize, last: usize) -> usize {
+        let offset = row * self.words;
+
+        let mut value = self.value(row, first) as isize;
+        let mut minimum = value;
+
+        // Bit i holds the difference between columns i and i + 1
+        let mut i = first;
+        while i < last {
+            let word = i / WORD_SIZE;
+            let end = min(last, (word + 1) * WORD_SIZE);
+
+            let mask = (u64::MAX >> (WORD_SIZE - (end - i))) << (i % WORD_SIZE);
+            let vp = self.vp[offset + word] & mask;
+            let vn = self.vn[offset + word] & mask;
+
+            let mut decreases = vn;
+            while decreases != 0 {
+                let below = (decreases ^ (decreases - 1)) & mask;
+
+                let candidate =
+                    value + (vp & below).count_ones() as isize - (vn & below).count_ones() as isize;
+                if candidate < minimum {
+                    minimum = candidate;
+                }
+
+                decreases &= decreases - 1;
+            }
+
+            value += vp.count_ones() as isize - vn.count_ones() as isize;
+            i = end;
+        }
+
+        return minimum as usize;
+    }
+}
+
+impl EditMatrix for MyersMatrix {
+    fn with_pattern<A: Alphabet>(pattern: &AlphabetPattern<A>, b: usize) -> Self {
+        return Self::new(pattern, b);
+    }
+
+    fn update_row<A: Alphabet>(
+        &mut self,
+        _pattern: &AlphabetPattern<A>,
+        row: usize,
+        c: AlphabetIndex
+    ) -> usize {
+        let (previous, current) = ((row - 1) * self.words, row * self.words);
+
+        // The distance to the empty pattern grows by one every row
+        let mut hin = 1;
+        for word in 0 .. self.words {
+            let eq = match self.peq.get(c as usize) {
+                Some(peq) => peq[word],
+                None => 0
+            };
+
+            let (mut vp, mut vn) = (self.vp[previous + word], self.vn[previous + word]);
+            hin = Self::step(&mut vp, &mut vn, eq, hin);
+
+            self.vp[current + word] = vp;
+            self.vn[current + word] = vn;
+        }
+
+        // Same band as the banded matrix
+        let first = max(1, row as isize - self.b as isize) as usize;
+        let last = min(self.pattern_size, self.b + row);
+
+        if first > last {
+            return usize::MAX;
+        }
+
+        return self.minimum(row, first, last);
+    }
+
+    fn in_final_column(&self, row: usize) -> bool {
+        return min(self.pattern_size, self.b + row) == self.pattern_size;
+    }
+
+    fn final_column(&self, row: usize) -> usize {
+        return self.value(row, self.pattern_size);
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        alphabet::{
+            AlphabetIndex,
+            AlphabetPattern,
+            AlphabetString,
+            DNAAlphabet
+        },
+        matrix::{
+            BandedMatrix,
+            EditMatrix
+        },
+        myers::MyersMatrix,
+        testing::{
+            random_text,
+            substitute
+        }
+    };
+
+    /// Compute the full edit distance matrix of a pattern and a text
+    fn naive_matrix(pattern: &[AlphabetIndex], text: &[AlphabetIndex]) -> Vec<Vec<usize>> {
+        let mut matrix = vec![vec![0; pattern.len() + 1]; text.len() + 1];
+
+        for row in 0 ..= text.len() {
+            for column in 0 ..= pattern.len() {
+                matrix[row][column] = match (row, column) {
+                    (0, _) => column,
+                    (_, 0) => row,
+                    _ => (matrix[row - 1][column - 1]
+                        + (text[row - 1] != pattern[column - 1]) as usize)
+                        .min(matrix[row - 1][column] + 1)
+                        .min(matrix[row][column - 1] + 1)
+                };
+            }
+        }
+
+        return matrix;
+    }
+
+    /// Get the characters of a pattern in its direction
+    fn pattern_indices(pattern: &AlphabetPattern<DNAAlphabet>) -> Vec<AlphabetIndex> {
+        return (0 .. pattern.len()).map(|i| pattern[i]).collect();
+    }
+
+    #[test]
+    fn test_update_row() {
+        let pattern = AlphabetPattern::<DNAAlphabet>::from("ACAAGT");
+        let text = AlphabetString::<DNAAlphabet>::from("ACTAGT");
+
+        let mut matrix = MyersMatrix::new(&pattern, 1);
+        let naive = naive_matrix(&pattern_indices(&pattern), &text);
+
+        for row in 1 ..= text.len() {
+            matrix.update_row(&pattern, row, text[row - 1]);
+
+            for column in 0 ..= pattern.len() {
+                assert_eq!(matrix.value(row, column), naive[row][column]);
+            }
+        }
+
+        assert_eq!(matrix.final_column(6), 1);
+    }
+
+    #[test]
+    fn test_banded_matrix() {
+        let mut rng = StdRng::seed_from_u64(25);
+
+        for &pattern_size in [1, 5, 20, 63, 64, 65, 150].iter() {
+            for b in 0 ..= 4 {
+                let pattern_string = random_text(&mut rng, pattern_size);
+                let pattern = AlphabetPattern::<DNAAlphabet>::from(pattern_string.as_str());
+
+                // Texts similar to the pattern, so the band does not run out immediately
+                let substitutions = rng.gen_range(0 ..= b + 1);
+                let text = substitute(&mut rng, &pattern_string, substitutions);
+                let mut text = AlphabetString::<DNAAlphabet>::from(text.as_str()).to_vec();
+                text.truncate(pattern_size + b);
+
+                let naive = naive_matrix(&pattern_indices(&pattern), &text);
+
+                let mut banded = BandedMatrix::new(pattern.len(), b);
+                let mut myers = MyersMatrix::new(&pattern, b);
+
+                for row in 1 ..= text.len() {
+                    let banded_minimum = banded.update_row(&pattern, row, text[row - 1]);
+                    let myers_minimum =
+                        EditMatrix::update_row(&mut myers, &pattern, row, text[row - 1]);
+
+                    assert_eq!(banded_minimum <= b, myers_minimum <= b);
+                    assert_eq!(myers.in_final_column(row), banded.in_final_column(row));
+                    assert_eq!(myers.final_column(row), naive[row][pattern_size]);
+
+                    if banded.in_final_column(row) && banded.final_column(row) <= b {
+                        assert_eq!(myers.final_column(row), banded.final_column(row));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Helpers to run the stages of the index construction on multiple threads. Without the
+//! `parallel` feature everything runs on the calling thread.
+
+use std::ops::Range;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Run a closure on a pool with the given amount of threads
+pub fn install<R: Send, F: FnOnce() -> R + Send>(threads: usize, f: F) -> R {
+    #[cfg(feature = "parallel")]
+    if threads > 1 {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Unable to create a thread pool");
+
+        return pool.install(f);
+    }
+
+    let _ = threads;
+    return f();
+}
+
+/// Get the amount of threads available to the current stage
+pub fn threads() -> usize {
+    #[cfg(feature = "parallel")]
+    if rayon::current_thread_index().is_some() {
+        return rayon::current_num_threads();
+    }
+
+    return 1;
+}
+
+/// Run two closures, concurrently if possible
+pub fn join<RA: Send, RB: Send>(
+    a: impl FnOnce() -> RA + Send,
+    b: impl FnOnce() -> RB + Send
+) -> (RA, RB) {
+    #[cfg(feature = "parallel")]
+    if threads() > 1 {
+        return rayon::join(a, b);
+    }
+
+    return (a(), b());
+}
+
+/// Split `0 .. n` into a chunk per thread and map every chunk. Chunks start at multiples of
+/// `align` and the results are returned in order.
+pub fn map_chunks<R: Send, F: Fn(Range<usize>) -> R + Send + Sync>(
+    n: usize,
+    align: usize,
+    f: F
+) -> Vec<R> {
+    let chunk_size = n.div_ceil(threads()).max(1);
+    let chunk_size = chunk_size.div_ceil(align) * align;
+
+    let chunks: Vec<Range<usize>> = (0 .. n)
+        .step_by(chunk_size)
+        .map(|start| start .. n.min(start + chunk_size))
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    if chunks.len() > 1 {
+        return chunks.into_par_iter().map(f).collect();
+    }
+
+    return chunks.into_iter().map(f).collect();
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel::{
+        install,
+        join,
+        map_chunks
+    };
+
+    #[test]
+    fn test_map_chunks() {
+        for threads in [1, 3, 8] {
+            let chunks = install(threads, || map_chunks(1000, 64, |chunk| chunk));
+
+            // The chunks are aligned and cover the range in order
+            let mut end = 0;
+            for chunk in chunks {
+                assert_eq!(chunk.start, end);
+                assert_eq!(chunk.start % 64, 0);
+                end = chunk.end;
+            }
+            assert_eq!(end, 1000);
+        }
+
+        assert!(map_chunks(0, 64, |chunk| chunk).is_empty());
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(install(2, || join(|| 1, || 2)), (1, 2));
+    }
+}
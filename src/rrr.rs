@@ -0,0 +1,457 @@
+use std::mem;
+
+use bitintr::Popcnt;
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::bitvector::{
+    Bitvec,
+    RankSelect
+};
+
+/// Amount of bits in a block
+const BLOCK_SIZE: usize = 15;
+
+/// Amount of blocks in a superblock
+const SUPERBLOCK_SIZE: usize = 32;
+
+/// Amount of classes stored in a word
+const CLASSES_PER_WORD: usize = 16;
+
+/// Binomial coefficients up to the block size
+const BINOMIALS: [[usize; BLOCK_SIZE + 1]; BLOCK_SIZE + 1] = binomials();
+
+/// Amount of bits needed for the offset of a block of every class
+const OFFSET_BITS: [usize; BLOCK_SIZE + 1] = offset_bits();
+
+const fn binomials() -> [[usize; BLOCK_SIZE + 1]; BLOCK_SIZE + 1] {
+    let mut table = [[0; BLOCK_SIZE + 1]; BLOCK_SIZE + 1];
+
+    let mut n = 0;
+    while n <= BLOCK_SIZE {
+        table[n][0] = 1;
+
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+
+        n += 1;
+    }
+
+    return table;
+}
+
+const fn offset_bits() -> [usize; BLOCK_SIZE + 1] {
+    let mut bits = [0; BLOCK_SIZE + 1];
+
+    let mut class = 0;
+    while class <= BLOCK_SIZE {
+        while (1 << bits[class]) < BINOMIALS[BLOCK_SIZE][class] {
+            bits[class] += 1;
+        }
+
+        class += 1;
+    }
+
+    return bits;
+}
+
+/// Get the position of the `k`th set bit in a word
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0 .. k {
+        word &= word - 1;
+    }
+
+    return word.trailing_zeros() as usize;
+}
+
+// ======================================================================
+// == RRRBitvec
+// ======================================================================
+
+/// Compressed bitvector of Raman, Raman and Rao. The bitvector is split in blocks of
+/// `BLOCK_SIZE` bits, which are stored as their class (the amount of set bits) and their offset
+/// among all blocks of that class. Sparse and dense bitvectors take far less than n bits.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RRRBitvec {
+    /// Size of the bitvector
+    n: usize,
+
+    /// Amount of set bits
+    ones: usize,
+
+    /// Class of every block, packed 4 bits per class
+    classes: Vec<u64>,
+
+    /// Offsets of the blocks, every offset takes the amount of bits needed for its class
+    offsets: Vec<u64>,
+
+    /// Amount of set bits before every superblock
+    ranks: Vec<usize>,
+
+    /// Position of the first offset of every superblock
+    pointers: Vec<usize>
+}
+
+impl RRRBitvec {
+    /// Get the offset of a block among the blocks of its class
+    fn encode(block: u64, class: usize) -> u64 {
+        let mut offset = 0;
+        let mut k = class;
+
+        for i in 0 .. BLOCK_SIZE {
+            if block >> i & 1 == 1 {
+                // Skip all blocks with the same set bits so far but this bit unset
+                offset += BINOMIALS[BLOCK_SIZE - i - 1][k];
+                k -= 1;
+            }
+        }
+
+        return offset as u64;
+    }
+
+    /// Get the block with a given class and offset
+    fn decode(class: usize, offset: u64) -> u64 {
+        let mut block = 0;
+        let mut offset = offset as usize;
+        let mut k = class;
+
+        for i in 0 .. BLOCK_SIZE {
+            if k == 0 {
+                break;
+            }
+
+            let unset = BINOMIALS[BLOCK_SIZE - i - 1][k];
+            if offset >= unset {
+                block |= 1 << i;
+                offset -= unset;
+                k -= 1;
+            }
+        }
+
+        return block;
+    }
+
+    /// Get the class of a block
+    fn class(&self, block: usize) -> usize {
+        let word = self.classes[block / CLASSES_PER_WORD];
+        return (word >> (block % CLASSES_PER_WORD * 4) & 0xF) as usize;
+    }
+
+    /// Read an offset from the offsets
+    fn read_offset(&self, pos: usize, len: usize) -> u64 {
+        if len == 0 {
+            return 0;
+        }
+
+        let w = pos / 64;
+        let b = pos % 64;
+
+        let mut offset = self.offsets[w] >> b;
+        if b + len > 64 {
+            offset |= self.offsets[w + 1] << (64 - b);
+        }
+
+        return offset & ((1 << len) - 1);
+    }
+
+    /// Append an offset to the offsets
+    fn write_offset(offsets: &mut Vec<u64>, pos: usize, offset: u64, len: usize) {
+        if len == 0 {
+            return;
+        }
+
+        let w = pos / 64;
+        let b = pos % 64;
+
+        if w == offsets.len() {
+            offsets.push(0);
+        }
+        offsets[w] |= offset << b;
+
+        if b + len > 64 {
+            offsets.push(offset >> (64 - b));
+        }
+    }
+
+    /// Get the amount of set bits before a block and the position of its offset
+    fn locate(&self, block: usize) -> (usize, usize) {
+        let superblock = block / SUPERBLOCK_SIZE;
+
+        let mut rank = self.ranks[superblock];
+        let mut pointer = self.pointers[superblock];
+
+        for b in superblock * SUPERBLOCK_SIZE .. block {
+            let class = self.class(b);
+
+            rank += class;
+            pointer += OFFSET_BITS[class];
+        }
+
+        return (rank, pointer);
+    }
+
+    /// Get the bits of a block whose offset is at a given position
+    fn block(&self, block: usize, pointer: usize) -> u64 {
+        let class = self.class(block);
+        return Self::decode(class, self.read_offset(pointer, OFFSET_BITS[class]));
+    }
+
+    /// Find the `k`th bit of a kind, given the amount of such bits before every superblock and
+    /// in every block
+    fn select<F, G, H>(
+        &self,
+        mut k: usize,
+        superblock_counts: F,
+        block_counts: G,
+        block_bits: H
+    ) -> usize
+    where
+        F: Fn(usize) -> usize,
+        G: Fn(usize) -> usize,
+        H: Fn(u64) -> u64
+    {
+        // Binary search for the last superblock with at most k bits before it
+        let mut first = 0;
+        let mut last = self.ranks.len() - 1;
+        while first < last {
+            let middle = (first + last).div_ceil(2);
+            if superblock_counts(middle) <= k {
+                first = middle;
+            } else {
+                last = middle - 1;
+            }
+        }
+
+        k -= superblock_counts(first);
+
+        // Scan the blocks of the superblock
+        let mut block = first * SUPERBLOCK_SIZE;
+        let mut pointer = self.pointers[first];
+        loop {
+            let class = self.class(block);
+            let count = block_counts(class);
+
+            if k < count {
+                let bits = block_bits(self.block(block, pointer));
+                return block * BLOCK_SIZE + select_in_word(bits, k);
+            }
+
+            k -= count;
+            pointer += OFFSET_BITS[class];
+            block += 1;
+        }
+    }
+}
+
+impl RankSelect for RRRBitvec {
+    fn from_bitvec(bitvector: Bitvec) -> Self {
+        let n = bitvector.len();
+        let blocks = n.div_ceil(BLOCK_SIZE);
+
+        let mut classes = vec![0; blocks.div_ceil(CLASSES_PER_WORD)];
+        let mut offsets = vec![];
+        let mut ranks = vec![];
+        let mut pointers = vec![];
+
+        let mut ones = 0;
+        let mut pointer = 0;
+        for block in 0 .. blocks {
+            if block % SUPERBLOCK_SIZE == 0 {
+                ranks.push(ones);
+                pointers.push(pointer);
+            }
+
+            let bits = bitvector.bits(block * BLOCK_SIZE, BLOCK_SIZE);
+            let class = bits.popcnt() as usize;
+
+            classes[block / CLASSES_PER_WORD] |= (class as u64) << (block % CLASSES_PER_WORD * 4);
+            Self::write_offset(
+                &mut offsets,
+                pointer,
+                Self::encode(bits, class),
+                OFFSET_BITS[class]
+            );
+
+            ones += class;
+            pointer += OFFSET_BITS[class];
+        }
+
+        // Every bitvector has at least one superblock
+        if ranks.is_empty() {
+            ranks.push(0);
+            pointers.push(0);
+        }
+
+        return Self {
+            n:        n,
+            ones:     ones,
+            classes:  classes,
+            offsets:  offsets,
+            ranks:    ranks,
+            pointers: pointers
+        };
+    }
+
+    fn len(&self) -> usize {
+        return self.n;
+    }
+
+    fn get(&self, pos: usize) -> bool {
+        let block = pos / BLOCK_SIZE;
+        let (_, pointer) = self.locate(block);
+
+        return self.block(block, pointer) >> (pos % BLOCK_SIZE) & 1 == 1;
+    }
+
+    fn rank(&self, pos: usize) -> usize {
+        if pos >= self.n {
+            return self.ones;
+        }
+
+        let block = pos / BLOCK_SIZE;
+        let (rank, pointer) = self.locate(block);
+
+        let bits = self.block(block, pointer) & ((1 << (pos % BLOCK_SIZE)) - 1);
+        return rank + bits.popcnt() as usize;
+    }
+
+    fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+
+        return Some(self.select(
+            k,
+            |superblock| self.ranks[superblock],
+            |class| class,
+            |bits| bits
+        ));
+    }
+
+    fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.n - self.ones {
+            return None;
+        }
+
+        // The unset bits after the end of the bitvector are never reached
+        return Some(self.select(
+            k,
+            |superblock| superblock * SUPERBLOCK_SIZE * BLOCK_SIZE - self.ranks[superblock],
+            |class| BLOCK_SIZE - class,
+            |bits| !bits & ((1 << BLOCK_SIZE) - 1)
+        ));
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        return (self.classes.len() + self.offsets.len()) * mem::size_of::<u64>()
+            + (self.ranks.len() + self.pointers.len()) * mem::size_of::<usize>();
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        bitvector::{
+            Bitvec,
+            RankSelect
+        },
+        rrr::{
+            RRRBitvec,
+            BLOCK_SIZE,
+            OFFSET_BITS
+        }
+    };
+
+    fn random_bitvector(rng: &mut StdRng, n: usize, density: f64) -> Bitvec {
+        let mut bitvector = Bitvec::new(n);
+
+        for i in 0 .. n {
+            if rng.gen_bool(density) {
+                bitvector.set(i, true);
+            }
+        }
+
+        return bitvector;
+    }
+
+    #[test]
+    fn test_offset_bits() {
+        assert_eq!(OFFSET_BITS[0], 0);
+        assert_eq!(OFFSET_BITS[1], 4);
+        assert_eq!(OFFSET_BITS[7], 13);
+        assert_eq!(OFFSET_BITS[15], 0);
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let mut seen = vec![vec![false; 1 << 13]; BLOCK_SIZE + 1];
+
+        for block in 0 .. 1u64 << BLOCK_SIZE {
+            let class = block.count_ones() as usize;
+            let offset = RRRBitvec::encode(block, class);
+
+            assert!(offset < 1 << OFFSET_BITS[class].max(1));
+            assert!(!seen[class][offset as usize]);
+            seen[class][offset as usize] = true;
+
+            assert_eq!(RRRBitvec::decode(class, offset), block);
+        }
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let mut rng = StdRng::seed_from_u64(16);
+
+        for (n, density) in
+            [(0, 0.5), (1, 0.5), (15, 0.5), (1000, 0.01), (50_000, 0.5), (50_000, 0.99)]
+        {
+            let mut bitvector = random_bitvector(&mut rng, n, density);
+            let rrr = RRRBitvec::from_bitvec(bitvector.clone());
+            bitvector.calculate_counts();
+
+            assert_eq!(rrr.len(), n);
+
+            for i in 0 .. n {
+                assert_eq!(rrr.get(i), bitvector.get(i));
+            }
+
+            for i in 0 ..= n {
+                assert_eq!(rrr.rank(i), bitvector.rank(i));
+                assert_eq!(rrr.rank0(i), bitvector.rank0(i));
+            }
+
+            let ones = bitvector.rank(n);
+            for k in 0 ..= ones {
+                assert_eq!(rrr.select1(k), bitvector.select1(k));
+            }
+            for k in 0 ..= n - ones {
+                assert_eq!(rrr.select0(k), bitvector.select0(k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_size_in_bytes() {
+        let mut rng = StdRng::seed_from_u64(16);
+
+        let bitvector = random_bitvector(&mut rng, 1_000_000, 0.01);
+        let rrr = RRRBitvec::from_bitvec(bitvector.clone());
+
+        assert!(rrr.size_in_bytes() * 2 < bitvector.size_in_bytes());
+    }
+}
@@ -1,54 +1,734 @@
-use std::ops::Index;
+use std::{
+    fmt,
+    mem,
+    ops::{
+        Index,
+        Range
+    }
+};
 
+use num_traits::int::PrimInt;
 use serde::{
     Deserialize,
     Serialize
 };
 pub use suffix_array::SuffixArray;
 
-use crate::bitvector::Bitvec;
+use crate::{
+    alphabet::AlphabetIndex,
+    bitvector::{
+        Bitvec,
+        RankSelect
+    },
+    construction::MemoryTracker,
+    errors::{
+        ErrorKind,
+        Result
+    },
+    parallel
+};
+
+// ======================================================================
+// == TextPosition
+// ======================================================================
+
+/// Integer type used to store positions in the text
+pub trait TextPosition: PrimInt + Send + Sync + fmt::Debug {
+    fn from_usize(i: usize) -> Self {
+        match Self::from(i) {
+            Some(position) => position,
+            None => panic!("{} does not fit in the text position type!", i)
+        }
+    }
+
+    fn as_usize(self) -> usize {
+        match self.to_usize() {
+            Some(i) => i,
+            None => panic!("{:?} does not fit in a usize!", self)
+        }
+    }
+}
+
+impl TextPosition for u32 {}
+
+impl TextPosition for u64 {}
+
+// ======================================================================
+// == Symbols
+// ======================================================================
+
+/// String of symbols to construct a suffix array for
+pub trait Symbols {
+    /// Get the amount of symbols
+    fn len(&self) -> usize;
+
+    /// Get the symbol at a position
+    fn symbol(&self, i: usize) -> usize;
+
+    /// Check whether there are no symbols
+    fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+}
+
+impl Symbols for [AlphabetIndex] {
+    fn len(&self) -> usize {
+        return <[AlphabetIndex]>::len(self);
+    }
+
+    fn symbol(&self, i: usize) -> usize {
+        return self[i] as usize;
+    }
+}
+
+/// The symbols of a text in reversed order
+pub struct Reversed<'a>(pub &'a [AlphabetIndex]);
+
+impl<'a> Symbols for Reversed<'a> {
+    fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    fn symbol(&self, i: usize) -> usize {
+        return self.0[self.0.len() - 1 - i] as usize;
+    }
+}
+
+/// Names of the substrings in a reduced problem, the sentinel name 0 is left out
+pub(crate) struct Names<'a, T: TextPosition>(pub(crate) &'a [T]);
+
+impl<'a, T: TextPosition> Symbols for Names<'a, T> {
+    fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    fn symbol(&self, i: usize) -> usize {
+        return self.0[i].as_usize() - 1;
+    }
+}
+
+// ======================================================================
+// == SA-IS
+// ======================================================================
+
+/// Construct the suffix array of a text with symbols in `0 .. sigma` using SA-IS. The empty
+/// suffix is included and sorts first.
+///
+/// The largest value of `T` marks empty slots during the construction, so the text needs fewer
+/// than `T::max_value()` characters: `u32` holds texts of up to 2^32 - 2 characters, longer texts
+/// need `u64`.
+pub fn construct<T: TextPosition, S: Symbols + Sync + ?Sized>(
+    text: &S,
+    sigma: usize,
+    memory: &MemoryTracker
+) -> Result<Vec<T>> {
+    if let Some(max_length) = T::max_value().to_usize() {
+        if text.len() >= max_length {
+            bail!(ErrorKind::TextTooLong(text.len(), max_length - 1));
+        }
+    }
+
+    memory.allocate((text.len() + 1) * mem::size_of::<T>())?;
+
+    let mut sa = vec![T::zero(); text.len() + 1];
+    sais(text, sigma, &mut sa, memory)?;
+
+    return Ok(sa);
+}
+
+/// Induced sorting of the suffixes, `sa` has room for the empty suffix. Classifying, counting and
+/// naming run per chunk of the text, placing and inducing the suffixes is sequential.
+fn sais<T: TextPosition, S: Symbols + Sync + ?Sized>(
+    text: &S,
+    sigma: usize,
+    sa: &mut [T],
+    memory: &MemoryTracker
+) -> Result<()> {
+    let n = text.len();
+    let empty = T::max_value();
+
+    if n == 0 {
+        sa[0] = T::zero();
+        return Ok(());
+    }
+
+    let types = classify(text);
+    memory.allocate(types.size_in_bytes())?;
+
+    // Bucket sizes and boundaries for every symbol
+    let buckets_size = 2 * sigma * mem::size_of::<usize>();
+    memory.allocate(buckets_size)?;
+
+    let sizes = count_symbols(text, sigma, memory)?;
+    let mut buckets = vec![0; sigma];
+
+    // Sort the LMS substrings
+    sa.fill(empty);
+
+    bucket_ends(&sizes, &mut buckets);
+    for i in 1 .. n {
+        if is_lms(&types, i) {
+            let c = text.symbol(i);
+            buckets[c] -= 1;
+            sa[buckets[c]] = T::from_usize(i);
+        }
+    }
+    sa[0] = T::from_usize(n);
+
+    induce(text, &types, &sizes, &mut buckets, sa);
+
+    // Move the sorted LMS positions to the front
+    let mut m = 0;
+    for i in 0 ..= n {
+        let p = sa[i];
+        if p != empty && is_lms(&types, p.as_usize()) {
+            sa[m] = p;
+            m += 1;
+        }
+    }
+    sa[m ..].fill(empty);
+
+    // Compare the neighbouring LMS substrings, bit i is set when substring i gets a new name
+    let new_names = Bitvec::concat(parallel::map_chunks(m, 64, |chunk| {
+        let mut new_names = Bitvec::new(chunk.len());
+
+        for i in chunk.clone() {
+            if i > 0 && !lms_equal(text, &types, sa[i - 1].as_usize(), sa[i].as_usize()) {
+                new_names.set(i - chunk.start, true);
+            }
+        }
+
+        return new_names;
+    }));
+    memory.allocate(new_names.size_in_bytes())?;
+
+    // Name the LMS substrings, LMS positions are at least two apart
+    let mut name = 0;
+    for i in 0 .. m {
+        let p = sa[i].as_usize();
+        name += new_names.get(i) as usize;
+        sa[m + p / 2] = T::from_usize(name);
+    }
+
+    memory.free(new_names.size_in_bytes());
+
+    // Gather the names in text order at the end of the array
+    let mut j = n + 1;
+    for i in (m ..= n).rev() {
+        if sa[i] != empty {
+            j -= 1;
+            sa[j] = sa[i];
+        }
+    }
+
+    // Sort the reduced problem, recursively if the names are not unique
+    {
+        let (reduced_sa, rest) = sa.split_at_mut(m);
+        let reduced = &rest[rest.len() - m ..];
+
+        if name + 1 < m {
+            sais(&Names(&reduced[.. m - 1]), name, reduced_sa, memory)?;
+        } else {
+            for i in 0 .. m {
+                reduced_sa[reduced[i].as_usize()] = T::from_usize(i);
+            }
+        }
+    }
+
+    // Translate the reduced suffix array to LMS positions
+    let mut j = n + 1 - m;
+    for i in 1 ..= n {
+        if is_lms(&types, i) {
+            sa[j] = T::from_usize(i);
+            j += 1;
+        }
+    }
+    for i in 0 .. m {
+        sa[i] = sa[n + 1 - m + sa[i].as_usize()];
+    }
+    sa[m ..].fill(empty);
+
+    // Place the sorted LMS suffixes at the ends of their buckets and sort all suffixes
+    bucket_ends(&sizes, &mut buckets);
+    for i in (1 .. m).rev() {
+        let p = sa[i];
+        sa[i] = empty;
+
+        let c = text.symbol(p.as_usize());
+        buckets[c] -= 1;
+        sa[buckets[c]] = p;
+    }
+
+    induce(text, &types, &sizes, &mut buckets, sa);
+
+    memory.free(buckets_size);
+    memory.free(types.size_in_bytes());
+
+    return Ok(());
+}
+
+/// Count the occurences of every symbol. Every chunk of the text is counted separately, which only
+/// pays off when the alphabet is small compared to the text.
+fn count_symbols<S: Symbols + Sync + ?Sized>(
+    text: &S,
+    sigma: usize,
+    memory: &MemoryTracker
+) -> Result<Vec<usize>> {
+    let n = text.len();
+
+    let count = |chunk: Range<usize>| {
+        let mut sizes = vec![0; sigma];
+        for i in chunk {
+            sizes[text.symbol(i)] += 1;
+        }
+
+        return sizes;
+    };
+
+    if sigma * parallel::threads() > n {
+        return Ok(count(0 .. n));
+    }
+
+    let counts_size = parallel::threads() * sigma * mem::size_of::<usize>();
+    memory.allocate(counts_size)?;
+
+    let mut sizes = vec![0; sigma];
+    for part in parallel::map_chunks(n, 1, count) {
+        for (size, count) in sizes.iter_mut().zip(part) {
+            *size += count;
+        }
+    }
+
+    memory.free(counts_size);
+
+    return Ok(sizes);
+}
+
+/// Classify the suffixes as S-type (set) or L-type, the empty suffix is S-type
+fn classify<S: Symbols + Sync + ?Sized>(text: &S) -> Bitvec {
+    let n = text.len();
+
+    let parts = parallel::map_chunks(n + 1, 64, |chunk| {
+        let mut types = Bitvec::new(chunk.len());
+
+        // Type of the suffix following the chunk, found by skipping over equal symbols
+        let mut next_type = chunk.end >= n || {
+            let (i, c) = (chunk.end, text.symbol(chunk.end));
+            let j = (i + 1 .. n).find(|j| text.symbol(*j) != c).unwrap_or(n);
+            j < n && c < text.symbol(j)
+        };
+
+        for i in chunk.clone().rev() {
+            if i == n {
+                next_type = true;
+            } else if i == n - 1 {
+                next_type = false;
+            } else {
+                let (a, b) = (text.symbol(i), text.symbol(i + 1));
+                next_type = a < b || (a == b && next_type);
+            }
+
+            types.set(i - chunk.start, next_type);
+        }
+
+        return types;
+    });
+
+    return Bitvec::concat(parts);
+}
+
+/// Check whether a suffix is a leftmost S-type suffix
+fn is_lms(types: &Bitvec, i: usize) -> bool {
+    return i > 0 && types.get(i) && !types.get(i - 1);
+}
+
+/// Check whether the LMS substrings starting at two positions are equal
+fn lms_equal<S: Symbols + Sync + ?Sized>(text: &S, types: &Bitvec, a: usize, b: usize) -> bool {
+    let n = text.len();
+
+    for k in 0 .. {
+        let (i, j) = (a + k, b + k);
+
+        // The sentinel is unique
+        if i == n || j == n {
+            return false;
+        }
+
+        if text.symbol(i) != text.symbol(j) || types.get(i) != types.get(j) {
+            return false;
+        }
+
+        if k > 0 && is_lms(types, i) {
+            return is_lms(types, j);
+        }
+    }
+
+    unreachable!();
+}
+
+/// Induce the order of the L-type and S-type suffixes from the placed suffixes
+fn induce<T: TextPosition, S: Symbols + Sync + ?Sized>(
+    text: &S,
+    types: &Bitvec,
+    sizes: &[usize],
+    buckets: &mut [usize],
+    sa: &mut [T]
+) {
+    let empty = T::max_value();
+
+    // L-type suffixes from left to right
+    bucket_starts(sizes, buckets);
+    for i in 0 .. sa.len() {
+        let p = sa[i];
+        if p != empty && p > T::zero() {
+            let j = p.as_usize() - 1;
+            if !types.get(j) {
+                let c = text.symbol(j);
+                sa[buckets[c]] = T::from_usize(j);
+                buckets[c] += 1;
+            }
+        }
+    }
+
+    // S-type suffixes from right to left
+    bucket_ends(sizes, buckets);
+    for i in (0 .. sa.len()).rev() {
+        let p = sa[i];
+        if p != empty && p > T::zero() {
+            let j = p.as_usize() - 1;
+            if types.get(j) {
+                let c = text.symbol(j);
+                buckets[c] -= 1;
+                sa[buckets[c]] = T::from_usize(j);
+            }
+        }
+    }
+}
+
+/// Calculate the start of every bucket, the empty suffix comes first
+fn bucket_starts(sizes: &[usize], buckets: &mut [usize]) {
+    let mut sum = 1;
+    for c in 0 .. sizes.len() {
+        buckets[c] = sum;
+        sum += sizes[c];
+    }
+}
+
+/// Calculate the end of every bucket, the empty suffix comes first
+fn bucket_ends(sizes: &[usize], buckets: &mut [usize]) {
+    let mut sum = 1;
+    for c in 0 .. sizes.len() {
+        sum += sizes[c];
+        buckets[c] = sum;
+    }
+}
+
+// ======================================================================
+// == SparseSuffixArray
+// ======================================================================
 
 /// Sparse suffix array for FM indices
 #[derive(Serialize, Deserialize, Debug)]
-pub struct SparseSuffixArray {
+pub struct SparseSuffixArray<T: TextPosition = u32, B: RankSelect = Bitvec> {
     /// Control vector to keep track of stored values
-    pub bitvector: Bitvec,
+    pub bitvector: B,
 
     /// The sparse suffix array
-    pub sparse_sa: Vec<u32>
+    pub sparse_sa: Vec<T>,
+
+    /// The rows of the text positions that are a multiple of the sparseness factor
+    pub sparse_isa: Vec<T>,
+
+    /// Distance between the sampled text positions
+    pub sparseness_factor: u32
 }
 
-impl SparseSuffixArray {
+impl<T: TextPosition, B: RankSelect> SparseSuffixArray<T, B> {
     /// Construct the sparse suffix array from the entire suffix array
-    pub fn from_sa(sa: &Vec<u32>, sparseness_factor: u32) -> Self {
-        let mut bitvector = Bitvec::new(sa.len());
-        let mut sparse_sa = Vec::new();
+    pub fn from_sa(sa: &Vec<T>, sparseness_factor: u32) -> Self {
+        let factor = T::from_usize(sparseness_factor as usize);
 
-        for i in 0 .. sa.len() {
-            if sa[i] % sparseness_factor == 0 {
-                sparse_sa.push(sa[i]);
-                bitvector.set(i, true);
+        let parts = parallel::map_chunks(sa.len(), 64, |chunk| {
+            let mut bitvector = Bitvec::new(chunk.len());
+            let mut sparse_sa = Vec::new();
+
+            for (i, value) in sa[chunk].iter().enumerate() {
+                if *value % factor == T::zero() {
+                    sparse_sa.push(*value);
+                    bitvector.set(i, true);
+                }
             }
-        }
 
-        bitvector.calculate_counts();
+            return (bitvector, sparse_sa);
+        });
+
+        let (bitvectors, samples): (Vec<Bitvec>, Vec<Vec<T>>) = parts.into_iter().unzip();
+
+        let bitvector = B::from_bitvec(Bitvec::concat(bitvectors));
+
+        // The inverse samples are the rows of the sampled values
+        let mut sparse_isa = vec![T::zero(); (sa.len() - 1) / sparseness_factor as usize + 1];
+        for (i, value) in sa.iter().enumerate() {
+            if *value % factor == T::zero() {
+                sparse_isa[(*value / factor).as_usize()] = T::from_usize(i);
+            }
+        }
 
         SparseSuffixArray {
-            bitvector,
-            sparse_sa
+            bitvector:         bitvector,
+            sparse_sa:         samples.concat(),
+            sparse_isa:        sparse_isa,
+            sparseness_factor: sparseness_factor
+        }
+    }
+
+    /// Get the amount of bytes used by the sparse suffix array
+    pub fn size_in_bytes(&self) -> usize {
+        return self.bitvector.size_in_bytes()
+            + (self.sparse_sa.len() + self.sparse_isa.len()) * mem::size_of::<T>();
+    }
+
+    /// Get the first sampled text position at or after a position together with its row. The end
+    /// of the text is treated as sampled, its empty suffix is always the first row.
+    pub fn next_isa_sample(&self, position: usize) -> (usize, usize) {
+        let n = self.bitvector.len() - 1;
+        let sparseness_factor = self.sparseness_factor as usize;
+
+        let j = position.div_ceil(sparseness_factor);
+        if j * sparseness_factor > n {
+            return (n, 0);
         }
+
+        return (j * sparseness_factor, self.sparse_isa[j].as_usize());
     }
 
     /// Check whether the sparse suffix array contains the value at a position
-    pub fn contains(&self, pos: u32) -> bool {
-        return self.bitvector[pos as usize];
+    pub fn contains(&self, pos: usize) -> bool {
+        return self.bitvector.get(pos);
     }
 }
 
-impl Index<usize> for SparseSuffixArray {
-    type Output = u32;
+impl<T: TextPosition, B: RankSelect> Index<usize> for SparseSuffixArray<T, B> {
+    type Output = T;
 
     fn index(&self, pos: usize) -> &Self::Output {
         return &self.sparse_sa[self.bitvector.rank(pos)];
     }
 }
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        alphabet::{
+            AlphabetIndex,
+            AlphabetString,
+            DNAAlphabet
+        },
+        construction::MemoryTracker,
+        errors::ErrorKind,
+        parallel,
+        rrr::RRRBitvec,
+        suffix_array::{
+            construct,
+            Reversed,
+            SparseSuffixArray,
+            SuffixArray,
+            Symbols
+        }
+    };
+
+    const INPUT: &str = "AACTAGGGCAATGTTCAACG";
+    const SA: [u32; 21] =
+        [20, 16, 0, 9, 17, 1, 4, 10, 15, 8, 18, 2, 19, 7, 6, 5, 12, 3, 14, 11, 13];
+
+    #[test]
+    fn test_construct() {
+        let text = AlphabetString::<DNAAlphabet>::from(INPUT);
+
+        let sa: Vec<u32> = construct(&text[..], 4, &MemoryTracker::new(None)).unwrap();
+        assert_eq!(sa, SA.to_vec());
+
+        let sa: Vec<u64> = construct(&text[..], 4, &MemoryTracker::new(None)).unwrap();
+        assert_eq!(sa, SA.iter().map(|i| *i as u64).collect::<Vec<u64>>());
+
+        let sa: Vec<u32> = construct(&[][..], 4, &MemoryTracker::new(None)).unwrap();
+        assert_eq!(sa, vec![0]);
+    }
+
+    #[test]
+    fn test_construct_random() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for sigma in [1, 2, 4, 20] {
+            for n in [1, 2, 3, 10, 100, 1000] {
+                let text: Vec<AlphabetIndex> = (0 .. n)
+                    .map(|_| rng.gen_range(0 .. sigma) as AlphabetIndex)
+                    .collect();
+
+                let sa: Vec<u32> = construct(&text[..], sigma, &MemoryTracker::new(None)).unwrap();
+
+                assert_eq!(sa, SuffixArray::new(&text).into_parts().1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_construct_repetitive() {
+        let text = AlphabetString::<DNAAlphabet>::from("AAAAAAAAAAAAAAAAAAAAACACACACACACACACACAC");
+
+        let sa: Vec<u64> = construct(&text[..], 4, &MemoryTracker::new(None)).unwrap();
+
+        let expected: Vec<u64> = SuffixArray::new(&text)
+            .into_parts()
+            .1
+            .iter()
+            .map(|i| *i as u64)
+            .collect();
+
+        assert_eq!(sa, expected);
+    }
+
+    #[test]
+    fn test_construct_threads() {
+        let mut rng = StdRng::seed_from_u64(10);
+
+        let random: Vec<AlphabetIndex> = (0 .. 5_000).map(|_| rng.gen_range(0 .. 4)).collect();
+        let repetitive: Vec<AlphabetIndex> = (0 .. 5_000).map(|i| [0, 1, 0, 2][i % 4]).collect();
+
+        for text in [random, repetitive] {
+            let expected: Vec<u32> = construct(&text[..], 4, &MemoryTracker::new(None)).unwrap();
+
+            for threads in [2, 5] {
+                let sa: Vec<u32> = parallel::install(threads, || {
+                    construct(&text[..], 4, &MemoryTracker::new(None)).unwrap()
+                });
+
+                assert_eq!(sa, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_construct_reversed() {
+        let text = AlphabetString::<DNAAlphabet>::from(INPUT);
+        let reversed: Vec<AlphabetIndex> = text.iter().rev().cloned().collect();
+
+        let sa: Vec<u32> = construct(&Reversed(&text), 4, &MemoryTracker::new(None)).unwrap();
+
+        assert_eq!(sa, SuffixArray::new(&reversed).into_parts().1);
+    }
+
+    #[test]
+    fn test_construct_memory_budget() {
+        let text = AlphabetString::<DNAAlphabet>::from(INPUT);
+
+        // The suffix array alone needs 21 * 8 bytes
+        let memory = MemoryTracker::new(Some(21 * 8));
+        match construct::<u64, _>(&text[..], 4, &memory) {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(_, 168) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+
+        let memory = MemoryTracker::new(Some(1024));
+        construct::<u64, _>(&text[..], 4, &memory).unwrap();
+
+        // Only the suffix array remains
+        assert_eq!(memory.current(), 21 * 8);
+        assert!(memory.stats().peak_memory > 21 * 8);
+    }
+
+    /// Text that only has a length, to check the bounds on it without allocating
+    struct Length(usize);
+
+    impl Symbols for Length {
+        fn len(&self) -> usize {
+            return self.0;
+        }
+
+        fn symbol(&self, _i: usize) -> usize {
+            return 0;
+        }
+    }
+
+    #[test]
+    fn test_construct_too_long() {
+        let memory = MemoryTracker::new(Some(0));
+
+        // The largest value of the position type is needed to mark empty slots
+        match construct::<u32, _>(&Length(u32::MAX as usize), 4, &memory) {
+            Err(e) => match e.kind() {
+                ErrorKind::TextTooLong(_, max_length) => {
+                    assert_eq!(*max_length, u32::MAX as usize - 1)
+                }
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the text length was not checked")
+        }
+
+        // Shorter texts only run into the memory budget
+        match construct::<u32, _>(&Length(u32::MAX as usize - 1), 4, &memory) {
+            Err(e) => match e.kind() {
+                ErrorKind::MemoryBudgetExceeded(..) => (),
+                _ => panic!("unexpected error: {}", e)
+            },
+            Ok(_) => panic!("the memory budget was not enforced")
+        }
+    }
+
+    #[test]
+    fn test_sparse_suffix_array() {
+        let sa: Vec<u64> = SA.iter().map(|i| *i as u64).collect();
+
+        let sparse_sa: SparseSuffixArray<u64> = SparseSuffixArray::from_sa(&sa, 3);
+
+        for i in 0 .. sa.len() {
+            assert_eq!(sparse_sa.contains(i), sa[i].is_multiple_of(3));
+
+            if sparse_sa.contains(i) {
+                assert_eq!(sparse_sa[i], sa[i]);
+            }
+        }
+
+        for position in 0 ..= 20 {
+            let (sample, row) = sparse_sa.next_isa_sample(position);
+
+            assert!(sample >= position && (sample % 3 == 0 || sample == 20));
+            assert!(sample < position + 3);
+            assert_eq!(sa[row], sample as u64);
+        }
+    }
+
+    #[test]
+    fn test_sparse_suffix_array_rrr() {
+        let mut rng = StdRng::seed_from_u64(16);
+
+        let text: Vec<AlphabetIndex> = (0 .. 100_000).map(|_| rng.gen_range(0 .. 4)).collect();
+        let sa = construct::<u32, _>(&text[..], 4, &MemoryTracker::new(None)).unwrap();
+
+        let sparse_sa: SparseSuffixArray = SparseSuffixArray::from_sa(&sa, 32);
+        let rrr_sparse_sa: SparseSuffixArray<u32, RRRBitvec> = SparseSuffixArray::from_sa(&sa, 32);
+
+        for i in 0 .. sa.len() {
+            assert_eq!(rrr_sparse_sa.contains(i), sparse_sa.contains(i));
+
+            if rrr_sparse_sa.contains(i) {
+                assert_eq!(rrr_sparse_sa[i], sa[i]);
+            }
+        }
+
+        assert!(rrr_sparse_sa.size_in_bytes() < sparse_sa.size_in_bytes());
+    }
+}
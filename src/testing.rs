@@ -0,0 +1,65 @@
+use rand::{
+    rngs::StdRng,
+    Rng
+};
+
+const NUCLEOTIDES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+/// Random DNA text of the given length
+pub fn random_text(rng: &mut StdRng, length: usize) -> String {
+    return (0 .. length).map(|_| random_nucleotide(rng)).collect();
+}
+
+/// Apply a number of random substitutions, insertions and deletions
+pub fn mutate(rng: &mut StdRng, pattern: &str, edits: usize) -> String {
+    let mut pattern: Vec<char> = pattern.chars().collect();
+
+    for _ in 0 .. edits {
+        let i = rng.gen_range(0 .. pattern.len());
+        match rng.gen_range(0 .. 3) {
+            0 => pattern[i] = random_nucleotide(rng),
+            1 => pattern.insert(i, random_nucleotide(rng)),
+            _ => drop(pattern.remove(i))
+        }
+    }
+
+    return pattern.into_iter().collect();
+}
+
+/// Apply a number of random substitutions, keeping the length
+pub fn substitute(rng: &mut StdRng, pattern: &str, substitutions: usize) -> String {
+    let mut pattern: Vec<char> = pattern.chars().collect();
+
+    for _ in 0 .. substitutions {
+        let i = rng.gen_range(0 .. pattern.len());
+        pattern[i] = random_nucleotide(rng);
+    }
+
+    return pattern.into_iter().collect();
+}
+
+/// Every start position in the text with at most k mismatches, with the
+/// positions of the mismatches in the pattern
+pub fn naive_hamming(text: &str, pattern: &str, k: usize) -> Vec<(usize, Vec<usize>)> {
+    if pattern.len() > text.len() {
+        return vec![];
+    }
+
+    return (0 ..= text.len() - pattern.len())
+        .map(|start| {
+            let mismatches = pattern
+                .bytes()
+                .zip(text[start ..].bytes())
+                .enumerate()
+                .filter(|(_, (a, b))| a != b)
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+            (start, mismatches)
+        })
+        .filter(|(_, mismatches)| mismatches.len() <= k)
+        .collect();
+}
+
+fn random_nucleotide(rng: &mut StdRng) -> char {
+    return NUCLEOTIDES[rng.gen_range(0 .. 4)];
+}
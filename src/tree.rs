@@ -3,11 +3,17 @@ use std::fmt;
 use crate::{
     alphabet::{
         Alphabet,
-        AlphabetIndex,
-        DNAAlphabet
+        AlphabetIndex
+    },
+    bitvector::{
+        Bitvec,
+        OccurenceTable,
+        Occurences,
+        RankSelect
     },
     index::fm_index::FMIndex,
-    range::Range
+    range::Range,
+    suffix_array::TextPosition
 };
 
 pub struct Position {
@@ -51,20 +57,20 @@ impl PartialEq for Position {
 
 impl fmt::Debug for Position {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // TODO: do not hardcode alphabet
-        write!(
-            f,
-            "range: {:?}, depth: {}, char: {}",
-            self.range,
-            self.depth,
-            DNAAlphabet::default().i2c(self.index) as char
-        )
+        // The position does not know its alphabet, so only the index can be shown
+        write!(f, "range: {:?}, depth: {}, index: {}", self.range, self.depth, self.index)
     }
 }
 
-pub struct SearchTree<'a, A: Alphabet> {
+pub struct SearchTree<
+    'a,
+    A: Alphabet,
+    T: TextPosition = u32,
+    O: Occurences = OccurenceTable,
+    B: RankSelect = Bitvec
+> {
     /// The fm index over which we span the tree
-    fm_index: &'a FMIndex<A>,
+    fm_index: &'a FMIndex<A, T, O, B>,
 
     /// The alphabet for this search tree
     alphabet: A,
@@ -73,13 +79,13 @@ pub struct SearchTree<'a, A: Alphabet> {
     search_space: Vec<Position>
 }
 
-impl<'a, A: Alphabet> SearchTree<'a, A> {
-    pub fn new(fm_index: &'a FMIndex<A>) -> Self {
+impl<'a, A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> SearchTree<'a, A, T, O, B> {
+    pub fn new(fm_index: &'a FMIndex<A, T, O, B>) -> Self {
         let search_space = vec![];
 
         Self {
             fm_index:     fm_index,
-            alphabet:     Default::default(),
+            alphabet:     fm_index.alphabet().clone(),
             search_space: search_space
         }
     }
@@ -95,7 +101,9 @@ impl<'a, A: Alphabet> SearchTree<'a, A> {
     }
 }
 
-impl<'a, A: Alphabet> Iterator for SearchTree<'a, A> {
+impl<'a, A: Alphabet, T: TextPosition, O: Occurences, B: RankSelect> Iterator
+    for SearchTree<'a, A, T, O, B>
+{
     type Item = Position;
 
     fn next(&mut self) -> Option<Position> {
@@ -110,7 +118,8 @@ mod tests {
             Alphabet,
             AlphabetIndex,
             AlphabetString,
-            DNAAlphabet
+            DNAAlphabet,
+            ProteinAlphabet
         },
         tree::{
             FMIndex,
@@ -121,6 +130,7 @@ mod tests {
     };
 
     const INPUT: &str = "AACTAGGGCAATGTTCAACG";
+    const PROTEIN_INPUT: &str = "MKWVTFISLLFLFSSAYSRGVFRRDTHKSE";
 
     #[test]
     fn test_position_new() {
@@ -161,6 +171,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extend_search_space_protein() {
+        let fm_index = FMIndex::new(AlphabetString::<ProteinAlphabet>::from(PROTEIN_INPUT), 1);
+
+        let mut search_tree = SearchTree::new(&fm_index);
+
+        // Characters C, N, P and Q do not occur in the input
+        let extend_results = vec![
+            (0, 1, 2),
+            (2, 2, 3),
+            (3, 3, 4),
+            (4, 4, 8),
+            (5, 8, 9),
+            (6, 9, 10),
+            (7, 10, 11),
+            (8, 11, 13),
+            (9, 13, 16),
+            (10, 16, 17),
+            (14, 17, 20),
+            (15, 20, 25),
+            (16, 25, 27),
+            (17, 27, 29),
+            (18, 29, 30),
+            (19, 30, 31),
+        ];
+
+        search_tree.extend_search_space(&Range::new(0, 31), 0);
+
+        assert_eq!(search_tree.search_space.len(), extend_results.len());
+
+        for i in 0 .. extend_results.len() {
+            assert_eq!(search_tree.search_space[i].character(), extend_results[i].0);
+            assert_eq!(search_tree.search_space[i].range().start, extend_results[i].1);
+            assert_eq!(search_tree.search_space[i].range().end, extend_results[i].2);
+            assert_eq!(search_tree.search_space[i].row(), 1);
+        }
+    }
+
     #[test]
     fn test_iterator() {
         let fm_index = FMIndex::new(AlphabetString::<DNAAlphabet>::from(INPUT), 1);
@@ -0,0 +1,567 @@
+use std::{
+    marker::PhantomData,
+    mem
+};
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::{
+    alphabet::{
+        Alphabet,
+        AlphabetIndex,
+        PackedAlphabetString
+    },
+    bitvector::{
+        Bitvec,
+        Occurences
+    }
+};
+
+// ======================================================================
+// == Shape
+// ======================================================================
+
+/// Shape of a wavelet tree, given by the code of every character
+pub trait Shape: Send + Sync {
+    /// Get the code of every character from the frequencies of the characters. A `false` sends
+    /// the character to the left child of a node, a `true` to the right child. The codes keep
+    /// the characters in order, so every subtree holds an interval of characters.
+    fn codes(frequencies: &[usize]) -> Vec<Vec<bool>>;
+}
+
+/// Assign the next bit to the characters from `start` up to `end`, sending those from `middle`
+/// on to the right child
+fn split_codes(codes: &mut [Vec<bool>], start: usize, middle: usize, end: usize) {
+    for (j, code) in codes.iter_mut().enumerate().take(end).skip(start) {
+        code.push(j >= middle);
+    }
+}
+
+/// Wavelet tree shape that halves the alphabet at every node
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Balanced;
+
+impl Balanced {
+    /// Split the characters in a range in halves
+    fn split(codes: &mut [Vec<bool>], start: usize, end: usize) {
+        if end - start < 2 {
+            return;
+        }
+
+        let middle = (start + end) / 2;
+        split_codes(codes, start, middle, end);
+
+        Self::split(codes, start, middle);
+        Self::split(codes, middle, end);
+    }
+}
+
+impl Shape for Balanced {
+    fn codes(frequencies: &[usize]) -> Vec<Vec<bool>> {
+        let mut codes = vec![vec![]; frequencies.len()];
+        Self::split(&mut codes, 0, frequencies.len());
+
+        return codes;
+    }
+}
+
+/// Wavelet tree shape that follows the frequencies of the characters in the BWT, so frequent
+/// characters have a short path to their leaf. A plain Huffman code mixes up the order of the
+/// characters, instead this is the optimum alphabetic code of Hu and Tucker: the shortest
+/// expected code length over all codes that keep the characters in order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Huffman;
+
+impl Huffman {
+    /// Split the characters in a range at the middle of its optimum subtree
+    fn split(codes: &mut [Vec<bool>], middles: &[Vec<usize>], start: usize, end: usize) {
+        if end - start < 2 {
+            return;
+        }
+
+        let middle = middles[start][end];
+        split_codes(codes, start, middle, end);
+
+        Self::split(codes, middles, start, middle);
+        Self::split(codes, middles, middle, end);
+    }
+}
+
+impl Shape for Huffman {
+    fn codes(frequencies: &[usize]) -> Vec<Vec<bool>> {
+        let sigma = frequencies.len();
+
+        // Total frequency of the characters before every character
+        let mut totals = vec![0; sigma + 1];
+        for (j, frequency) in frequencies.iter().enumerate() {
+            totals[j + 1] = totals[j] + frequency;
+        }
+
+        // Cost of the optimum subtree over the characters from `start` up to `end`, and the first
+        // character of its right child. Every level adds the frequencies below it once more.
+        let mut costs = vec![vec![0; sigma + 1]; sigma + 1];
+        let mut middles = vec![vec![0; sigma + 1]; sigma + 1];
+
+        for length in 2 ..= sigma {
+            for start in 0 ..= sigma - length {
+                let end = start + length;
+
+                let middle = (start + 1 .. end)
+                    .min_by_key(|middle| costs[start][*middle] + costs[*middle][end])
+                    .unwrap();
+
+                costs[start][end] =
+                    costs[start][middle] + costs[middle][end] + totals[end] - totals[start];
+                middles[start][end] = middle;
+            }
+        }
+
+        let mut codes = vec![vec![]; sigma];
+        Self::split(&mut codes, &middles, 0, sigma);
+
+        return codes;
+    }
+}
+
+// ======================================================================
+// == WaveletTree
+// ======================================================================
+
+/// Child of a wavelet tree node
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+enum Child {
+    Node(usize),
+    Leaf(AlphabetIndex)
+}
+
+/// Internal node of a wavelet tree
+#[derive(Serialize, Deserialize, Debug)]
+struct Node {
+    /// For every position in the node whether its character goes to the right child
+    bitvector: Bitvec,
+
+    /// The left and right child
+    children: [Child; 2],
+
+    /// Smallest character in the right child, the characters in the left child are all smaller
+    middle: AlphabetIndex
+}
+
+/// Occurence table that stores the BWT in a wavelet tree. It needs a single bitvector per level
+/// of the tree instead of one per character, which pays off for large alphabets.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WaveletTree<S: Shape = Balanced> {
+    /// Path from the root to the leaf of every character
+    codes: Vec<Vec<bool>>,
+
+    /// The root of the tree
+    root: Child,
+
+    /// The internal nodes
+    nodes: Vec<Node>,
+
+    /// Position of the sentinel character, which is left out of the tree
+    pub sentinel: usize,
+
+    /// The shape of the tree
+    shape: PhantomData<S>
+}
+
+/// Wavelet tree that halves the alphabet at every node
+pub type BalancedWaveletTree = WaveletTree<Balanced>;
+
+/// Wavelet tree shaped after the optimum alphabetic code of the BWT
+pub type HuffmanWaveletTree = WaveletTree<Huffman>;
+
+impl<S: Shape> WaveletTree<S> {
+    /// Build the subtree for a sequence of characters, returning its root
+    fn build(
+        nodes: &mut Vec<Node>,
+        codes: &Vec<Vec<bool>>,
+        characters: Vec<AlphabetIndex>,
+        sequence: Vec<AlphabetIndex>,
+        depth: usize
+    ) -> Child {
+        if characters.len() == 1 {
+            return Child::Leaf(characters[0]);
+        }
+
+        let mut bitvector = Bitvec::new(sequence.len());
+        let mut left_sequence = vec![];
+        let mut right_sequence = vec![];
+
+        for (i, char_i) in sequence.into_iter().enumerate() {
+            if codes[char_i as usize][depth] {
+                bitvector.set(i, true);
+                right_sequence.push(char_i);
+            } else {
+                left_sequence.push(char_i);
+            }
+        }
+
+        bitvector.calculate_counts();
+
+        // The characters stay sorted
+        let (left, right): (Vec<AlphabetIndex>, Vec<AlphabetIndex>) = characters
+            .into_iter()
+            .partition(|char_i| !codes[*char_i as usize][depth]);
+
+        // Reserve the place of the node before its children are added
+        let k = nodes.len();
+        nodes.push(Node {
+            bitvector: bitvector,
+            children:  [Child::Leaf(0); 2],
+            middle:    right[0]
+        });
+
+        nodes[k].children = [
+            Self::build(nodes, codes, left, left_sequence, depth + 1),
+            Self::build(nodes, codes, right, right_sequence, depth + 1)
+        ];
+
+        return Child::Node(k);
+    }
+
+    /// Translate a position in the BWT to a position in the tree, skipping the sentinel
+    fn position(&self, i: usize) -> usize {
+        return i - (self.sentinel < i) as usize;
+    }
+
+    /// Get the amount of positions before `i` that go to the given side of a node
+    fn rank(bitvector: &Bitvec, bit: bool, i: usize) -> usize {
+        let ones = bitvector.rank(i);
+
+        if bit {
+            return ones;
+        }
+        return i - ones;
+    }
+
+    /// Get the amount of characters smaller than `char_i` before position `i`, along with the
+    /// amount of visited nodes. Every subtree holds an interval of characters, so only the path
+    /// towards `char_i` is followed.
+    fn smaller(&self, i: usize, char_i: usize) -> (usize, usize) {
+        let mut i = i;
+        let mut child = self.root;
+        let mut smaller = 0;
+        let mut visited = 0;
+
+        while let Child::Node(k) = child {
+            let node = &self.nodes[k];
+            let bit = char_i >= node.middle as usize;
+
+            // The whole left child is smaller than char_i
+            if bit {
+                smaller += Self::rank(&node.bitvector, false, i);
+            }
+
+            i = Self::rank(&node.bitvector, bit, i);
+            child = node.children[bit as usize];
+            visited += 1;
+        }
+
+        if let Child::Leaf(leaf) = child {
+            if (leaf as usize) < char_i {
+                smaller += i;
+            }
+        }
+
+        return (smaller, visited);
+    }
+}
+
+impl<S: Shape> Occurences for WaveletTree<S> {
+    fn from_bwt<A: Alphabet>(bwt: &PackedAlphabetString<A>, sentinel: usize) -> Self {
+        let alphabet_length = bwt.alphabet.len();
+
+        let sequence: Vec<AlphabetIndex> = bwt
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != sentinel)
+            .map(|(_, char_i)| char_i)
+            .collect();
+
+        let mut frequencies = vec![0; alphabet_length];
+        for char_i in sequence.iter() {
+            frequencies[*char_i as usize] += 1;
+        }
+
+        let codes = S::codes(&frequencies);
+
+        let mut nodes = vec![];
+        let characters = (0 .. alphabet_length)
+            .map(|char_i| char_i as AlphabetIndex)
+            .collect();
+        let root = Self::build(&mut nodes, &codes, characters, sequence, 0);
+
+        return Self {
+            codes:    codes,
+            root:     root,
+            nodes:    nodes,
+            sentinel: sentinel,
+            shape:    PhantomData
+        };
+    }
+
+    fn occ(&self, char_i: usize, i: usize) -> usize {
+        let code = &self.codes[char_i];
+
+        let mut i = self.position(i);
+        let mut child = self.root;
+        let mut depth = 0;
+
+        while let Child::Node(k) = child {
+            let node = &self.nodes[k];
+
+            i = Self::rank(&node.bitvector, code[depth], i);
+            child = node.children[code[depth] as usize];
+            depth += 1;
+        }
+
+        return i;
+    }
+
+    fn cumulative_occ(&self, char_i: usize, i: usize) -> usize {
+        return self.smaller(self.position(i), char_i).0 + (self.sentinel < i) as usize;
+    }
+
+    fn symbol(&self, i: usize) -> AlphabetIndex {
+        if i == self.sentinel {
+            return 0;
+        }
+
+        let mut i = self.position(i);
+        let mut child = self.root;
+
+        loop {
+            match child {
+                Child::Leaf(char_i) => return char_i,
+                Child::Node(k) => {
+                    let node = &self.nodes[k];
+                    let bit = node.bitvector.get(i);
+
+                    i = Self::rank(&node.bitvector, bit, i);
+                    child = node.children[bit as usize];
+                }
+            }
+        }
+    }
+
+    fn sentinel(&self) -> usize {
+        return self.sentinel;
+    }
+
+    fn size_in_bytes(&self) -> usize {
+        let codes: usize = self.codes.iter().map(|code| code.len()).sum();
+        let nodes: usize = self
+            .nodes
+            .iter()
+            .map(|node| node.bitvector.size_in_bytes() + mem::size_of::<Node>())
+            .sum();
+
+        return codes + nodes;
+    }
+}
+
+// ======================================================================
+// == Tests
+// ======================================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use crate::{
+        alphabet::{
+            Alphabet,
+            AlphabetChar,
+            AlphabetIndex,
+            CustomAlphabet,
+            DNAAlphabet,
+            PackedAlphabetString,
+            ProteinAlphabet
+        },
+        bitvector::{
+            OccurenceTable,
+            Occurences
+        },
+        wavelet_tree::{
+            Balanced,
+            BalancedWaveletTree,
+            Huffman,
+            HuffmanWaveletTree,
+            Shape,
+            WaveletTree
+        }
+    };
+
+    const BWT: &str = "GCACAATATGAACGGATCTAG";
+    const SENTINEL_POS: usize = 2;
+
+    const PROTEIN_BWT: &str = "ESRSTLVLRTFHMLFSARSFSKIYFVDGWKA";
+    const PROTEIN_SENTINEL_POS: usize = 16;
+
+    fn assert_same_occurences<A: Alphabet, S: Shape>(
+        bwt: &PackedAlphabetString<A>,
+        sentinel: usize
+    ) {
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(bwt, sentinel);
+        let wavelet_tree = WaveletTree::<S>::from_bwt(bwt, sentinel);
+
+        assert_eq!(wavelet_tree.sentinel(), sentinel);
+
+        for i in 0 .. bwt.len() {
+            assert_eq!(wavelet_tree.symbol(i), occurence_table.symbol(i));
+        }
+
+        for i in 0 ..= bwt.len() {
+            for j in 0 .. bwt.alphabet.len() {
+                assert_eq!(wavelet_tree.occ(j, i), occurence_table.occ(j, i));
+                assert_eq!(wavelet_tree.cumulative_occ(j, i), occurence_table.cumulative_occ(j, i));
+            }
+        }
+    }
+
+    fn random_protein_bwt(
+        rng: &mut StdRng,
+        length: usize
+    ) -> PackedAlphabetString<ProteinAlphabet> {
+        // Skewed towards the first characters, so the Huffman tree is not balanced
+        let characters = b"ACDEFGHIKLMNPQRSTVWY";
+        let bwt: String = (0 .. length)
+            .map(|_| characters[rng.gen_range(0 .. 20).min(rng.gen_range(0 .. 20))] as char)
+            .collect();
+
+        return PackedAlphabetString::from(bwt.as_str());
+    }
+
+    #[test]
+    fn test_balanced_codes() {
+        let codes = Balanced::codes(&[0; 5]);
+
+        assert_eq!(
+            codes,
+            vec![
+                vec![false, false],
+                vec![false, true],
+                vec![true, false],
+                vec![true, true, false],
+                vec![true, true, true],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_huffman_codes() {
+        let codes = Huffman::codes(&[10, 1, 1, 3]);
+
+        assert_eq!(codes[0].len(), 1);
+        assert_eq!(codes[3].len(), 2);
+        assert_eq!(codes[1].len(), 3);
+        assert_eq!(codes[2].len(), 3);
+
+        // No code is a prefix of another code
+        for i in 0 .. codes.len() {
+            for j in 0 .. codes.len() {
+                assert!(i == j || !codes[j].starts_with(&codes[i]));
+            }
+        }
+
+        // The codes keep the characters in order, a plain Huffman code would cost 35 here
+        let frequencies = [1, 8, 1, 8, 1];
+        let codes = Huffman::codes(&frequencies);
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let cost: usize = (0 .. codes.len())
+            .map(|j| frequencies[j] * codes[j].len())
+            .sum();
+        assert_eq!(cost, 47);
+    }
+
+    #[test]
+    fn test_visited_nodes() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let bwt = random_protein_bwt(&mut rng, 10_000);
+
+        let huffman = HuffmanWaveletTree::from_bwt(&bwt, 0);
+        let depth = huffman.codes.iter().map(|code| code.len()).max().unwrap();
+
+        // A single path from the root to a leaf is followed
+        for char_i in 0 .. bwt.alphabet.len() {
+            let (_, visited) = huffman.smaller(5_000, char_i);
+            assert!(visited <= depth);
+            assert_eq!(visited, huffman.codes[char_i].len());
+        }
+    }
+
+    #[test]
+    fn test_occurences() {
+        let bwt = PackedAlphabetString::<DNAAlphabet>::from(BWT);
+
+        assert_same_occurences::<_, Balanced>(&bwt, SENTINEL_POS);
+        assert_same_occurences::<_, Huffman>(&bwt, SENTINEL_POS);
+    }
+
+    #[test]
+    fn test_occurences_protein() {
+        let bwt = PackedAlphabetString::<ProteinAlphabet>::from(PROTEIN_BWT);
+
+        assert_same_occurences::<_, Balanced>(&bwt, PROTEIN_SENTINEL_POS);
+        assert_same_occurences::<_, Huffman>(&bwt, PROTEIN_SENTINEL_POS);
+    }
+
+    #[test]
+    fn test_occurences_random() {
+        let mut rng = StdRng::seed_from_u64(13);
+
+        for length in [1, 2, 64, 1000] {
+            let bwt = random_protein_bwt(&mut rng, length);
+            let sentinel = rng.gen_range(0 .. length);
+
+            assert_same_occurences::<_, Balanced>(&bwt, sentinel);
+            assert_same_occurences::<_, Huffman>(&bwt, sentinel);
+        }
+    }
+
+    #[test]
+    fn test_occurences_large_alphabet() {
+        let mut rng = StdRng::seed_from_u64(13);
+
+        let characters: Vec<AlphabetChar> = (1 ..= 250).collect();
+        let alphabet = CustomAlphabet::new(&characters, false).unwrap();
+
+        for length in [1, 64, 2_000] {
+            // Skewed towards the first characters, so the Huffman tree is not balanced
+            let mut bwt = PackedAlphabetString::with_alphabet(length, alphabet.clone());
+            for i in 0 .. length {
+                let char_i = rng.gen_range(0 .. 250).min(rng.gen_range(0 .. 250));
+                bwt.set(i, char_i as AlphabetIndex);
+            }
+
+            let sentinel = rng.gen_range(0 .. length);
+
+            assert_same_occurences::<_, Balanced>(&bwt, sentinel);
+            assert_same_occurences::<_, Huffman>(&bwt, sentinel);
+        }
+    }
+
+    #[test]
+    fn test_size_in_bytes() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let bwt = random_protein_bwt(&mut rng, 100_000);
+
+        let occurence_table: OccurenceTable = OccurenceTable::from_bwt(&bwt, 0);
+        let balanced = BalancedWaveletTree::from_bwt(&bwt, 0);
+        let huffman = HuffmanWaveletTree::from_bwt(&bwt, 0);
+
+        assert!(balanced.size_in_bytes() < occurence_table.size_in_bytes());
+        assert!(huffman.size_in_bytes() < balanced.size_in_bytes());
+    }
+}